@@ -1,20 +1,41 @@
-use bevy::prelude::*;
+use bevy::{platform::collections::HashMap, prelude::*};
+
+use crate::{
+    difficulty::DifficultyLevel,
+    modes::GameMode,
+};
+
+const LEADERBOARD_SIZE: usize = 5;
+
+// Every mode and difficulty has its own leaderboard.
+pub type LeaderboardKey = (GameMode, DifficultyLevel);
 
 #[derive(Resource, Debug, Default)]
 pub struct AppGlobals {
     pub score: i32,
-    pub high_score: i32,
+    pub leaderboards: HashMap<LeaderboardKey, Vec<i32>>,  // best first
     pub last_score: i32,
     pub level: i32,
 }
 
 impl AppGlobals {
-    pub fn final_score_update(&mut self) {
+    pub fn leaderboard(&self, key: LeaderboardKey) -> &[i32] {
+        self.leaderboards.get(&key).map(|scores| scores.as_slice()).unwrap_or_default()
+    }
+
+    pub fn high_score(&self, key: LeaderboardKey) -> i32 {
+        self.leaderboard(key).first().copied().unwrap_or(0)
+    }
+
+    pub fn final_score_update(&mut self, key: LeaderboardKey) {
         // Process the final score at the end of a game.
         self.last_score = self.score;
 
-        if self.last_score > self.high_score {
-            self.high_score = self.last_score;
+        if self.last_score > 0 {
+            let scores = self.leaderboards.entry(key).or_default();
+            let rank = scores.partition_point(|&score| score >= self.last_score);
+            scores.insert(rank, self.last_score);
+            scores.truncate(LEADERBOARD_SIZE);
         }
 
         self.score = 0;
@@ -37,7 +58,7 @@ fn init_globals(
 ) {
     *app_globals = AppGlobals {
         score: 0,
-        high_score: 0,
+        leaderboards: HashMap::default(),
         last_score: 0,
         level: 1,
     }
@@ -1,58 +1,240 @@
-use bevy::prelude::*;
+use std::{collections::BTreeMap, sync::Mutex};
 
+use bevy::{
+    asset::{
+        io::Reader,
+        AssetLoader,
+        AssetPath,
+        LoadContext,
+        UntypedAssetId,
+    },
+    platform::collections::{HashMap, HashSet},
+    prelude::*,
+};
+use serde::Deserialize;
+
+use crate::state::GameState;
+
+// The manifest maps the logical ids used throughout the game to asset paths.
+// Paths inside a manifest are relative to the manifest file itself, so an
+// override directory can ship its own models & sounds next to its manifest.
+const MANIFEST_PATH: &str = "game.manifest.ron";
+
+
+// The asset manifest, loaded from `assets/game.manifest.ron`.
+//
+// Its layout looks like:
+//
+//   (
+//       scenes: { "spaceship": "SpaceshipNew.glb#Scene0", ... },
+//       audio: { "shooting_sound": "sound/Shoot-2.ogg", ... },
+//       fonts: { "font": "fonts/fira-sans.bold.ttf" },
+//       overrides: [ "mods/my_mod/game.manifest.ron" ],
+//   )
+//
+// Each entry in `overrides` is another manifest whose entries replace the
+// ones with the same id.  They are applied in order, so later overrides win.
+#[derive(Asset, TypePath, Debug, Default, Clone)]
+pub struct AssetManifest {
+    pub scenes: HashMap<String, String>,
+    pub audio: HashMap<String, String>,
+    pub fonts: HashMap<String, String>,
+}
+
+impl AssetManifest {
+    fn apply_override(&mut self, other: AssetManifest) {
+        self.scenes.extend(other.scenes);
+        self.audio.extend(other.audio);
+        self.fonts.extend(other.fonts);
+    }
+}
+
+// The manifest file as written, before its paths are resolved.  Unknown
+// sections are ignored, so older builds can still read newer manifests.
+#[derive(Deserialize, Debug, Default)]
+#[serde(default)]
+struct ManifestFile {
+    scenes: BTreeMap<String, String>,
+    audio: BTreeMap<String, String>,
+    fonts: BTreeMap<String, String>,
+    overrides: Vec<String>,
+}
+
+#[derive(Default)]
+pub struct AssetManifestLoader;
+
+impl AssetLoader for AssetManifestLoader {
+    type Asset = AssetManifest;
+    type Settings = ();
+    type Error = BevyError;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &(),
+        load_context: &mut LoadContext<'_>,
+    ) -> Result<AssetManifest, BevyError> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+
+        let file: ManifestFile = ron::de::from_bytes(&bytes)?;
+
+        let base_path = load_context.asset_path().clone();
+        let resolve = |entries: BTreeMap<String, String>| {
+            entries
+                .into_iter()
+                .map(|(id, path)| -> Result<(String, String), BevyError> {
+                    Ok((id, base_path.resolve_embed(&path)?.to_string()))
+                })
+                .collect::<Result<HashMap<_, _>, BevyError>>()
+        };
+
+        let mut manifest = AssetManifest {
+            scenes: resolve(file.scenes)?,
+            audio: resolve(file.audio)?,
+            fonts: resolve(file.fonts)?,
+        };
+
+        let overrides = file.overrides
+            .iter()
+            .map(|path| base_path.resolve_embed(path).map(|path| path.to_string()))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        for path in overrides {
+            // A missing mod shouldn't keep the game from starting, so we
+            // just complain about it and carry on with what we have.
+            match load_context.loader().immediate().load::<AssetManifest>(AssetPath::from(path.clone())).await {
+                Ok(loaded) => {
+                    info!("Applying asset overrides from {}", path);
+                    manifest.apply_override(loaded.take());
+                }
+                Err(err) => {
+                    warn!("Skipping asset overrides from {}: {}", path, err);
+                }
+            }
+        }
+
+        Ok(manifest)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["manifest.ron"]
+    }
+}
+
+
+#[derive(Resource, Debug, Default)]
+pub struct AssetManifestHandle {
+    pub handle: Handle<AssetManifest>,
+}
+
+// The loaded handles for everything in the manifest, keyed by logical id.
 #[derive(Resource, Debug, Default)]
 pub struct SceneAssets {
-    pub asteroid: Handle<Scene>,
-    pub asteroid_medium: Handle<Scene>,
-    pub asteroid_big: Handle<Scene>,
-    pub asteroid_debris: Handle<Scene>,
-    pub spaceship: Handle<Scene>,
-    pub shield: Handle<Scene>,
-    pub saucer: Handle<Scene>,
-    pub explosion: Handle<Scene>,
-    pub missiles: Handle<Scene>,
-    pub saucer_missile: Handle<Scene>,
-    pub shooting_sound: Handle<AudioSource>,
-    pub saucer_shooting_sound: Handle<AudioSource>,
-    pub meteor_hit_sound: Handle<AudioSource>,
-    pub thruster_sound: Handle<AudioSource>,
-    pub shield_ready_sound: Handle<AudioSource>,
-    pub saucer_sound: Handle<AudioSource>,
-    pub background_music: Handle<AudioSource>,
-    pub font: Handle<Font>,
+    pub scenes: HashMap<String, Handle<Scene>>,
+    pub audio: HashMap<String, Handle<AudioSource>>,
+    pub fonts: HashMap<String, Handle<Font>>,
+    pub is_populated: bool,
+    // The unknown ids we have already complained about.  Most lookups
+    // happen every time something is spawned, so without this the log
+    // would fill up with the same warning.
+    missing: Mutex<HashSet<(&'static str, String)>>,
+}
+
+impl SceneAssets {
+    pub fn scene(&self, id: &str) -> Handle<Scene> {
+        self.lookup(&self.scenes, "scene", id)
+    }
+
+    pub fn audio(&self, id: &str) -> Handle<AudioSource> {
+        self.lookup(&self.audio, "audio", id)
+    }
+
+    pub fn font(&self, id: &str) -> Handle<Font> {
+        self.lookup(&self.fonts, "font", id)
+    }
+
+    fn lookup<A: Asset>(&self, handles: &HashMap<String, Handle<A>>, kind: &'static str, id: &str) -> Handle<A> {
+        match handles.get(id) {
+            Some(handle) => handle.clone(),
+            None => {
+                // An unknown id is a programming (or modding) error, but it
+                // should not take the whole game down.  The default handle
+                // simply renders/plays nothing.
+                let first_time = self.missing
+                    .lock()
+                    .map(|mut missing| missing.insert((kind, id.to_string())))
+                    .unwrap_or(true);
+                if first_time {
+                    warn!("No {} asset with id '{}' in the manifest", kind, id);
+                }
+                Handle::default()
+            }
+        }
+    }
+
+    // Every handle we hand out to the rest of the game, along with its id
+    // so that the loading screen can tell us which one went missing.  Sorted
+    // by id, so any failures are listed the same way every time.
+    pub fn tracked_handles(&self) -> Vec<(String, UntypedAssetId)> {
+        let scenes = self.scenes.iter().map(|(id, h)| (id.clone(), h.id().untyped()));
+        let audio = self.audio.iter().map(|(id, h)| (id.clone(), h.id().untyped()));
+        let fonts = self.fonts.iter().map(|(id, h)| (id.clone(), h.id().untyped()));
+
+        let mut handles = scenes.chain(audio).chain(fonts).collect::<Vec<_>>();
+        handles.sort_by(|(a, _), (b, _)| a.cmp(b));
+        handles
+    }
 }
 
 pub struct AssetLoaderPlugin;
 
 impl Plugin for AssetLoaderPlugin {
     fn build(&self, app: &mut App) {
-        app.init_resource::<SceneAssets>()
-        .add_systems(PreStartup, load_assets);
+        app.init_asset::<AssetManifest>()
+        .init_asset_loader::<AssetManifestLoader>()
+        .init_resource::<AssetManifestHandle>()
+        .init_resource::<SceneAssets>()
+        .add_systems(OnEnter(GameState::Loading), load_manifest)
+        .add_systems(Update, load_assets.run_if(in_state(GameState::Loading)));
     }
 }
 
-fn load_assets(
+// Runs again whenever the player asks to retry a manifest that failed to
+// load; a failed handle is loaded afresh rather than left failed.
+fn load_manifest(
+    mut manifest_handle: ResMut<AssetManifestHandle>,
+    asset_server: Res<AssetServer>,
+) {
+    manifest_handle.handle = asset_server.load(MANIFEST_PATH);
+}
+
+// Once the manifest is in, kick off loading everything it lists.
+pub fn load_assets(
     mut scene_assets: ResMut<SceneAssets>,
-    asset_server: Res<AssetServer>
+    manifest_handle: Res<AssetManifestHandle>,
+    manifests: Res<Assets<AssetManifest>>,
+    asset_server: Res<AssetServer>,
 ) {
-    *scene_assets = SceneAssets {
-        asteroid: asset_server.load(GltfAssetLabel::Scene(0).from_asset("Rock-0.glb")),
-        asteroid_medium: asset_server.load(GltfAssetLabel::Scene(0).from_asset("Rock-1.glb")),
-        asteroid_big: asset_server.load(GltfAssetLabel::Scene(0).from_asset("Rock-2.glb")),
-        asteroid_debris: asset_server.load(GltfAssetLabel::Scene(0).from_asset("Rock-Debris.glb")),
-        spaceship: asset_server.load(GltfAssetLabel::Scene(0).from_asset("SpaceshipNew.glb")),
-        shield: asset_server.load(GltfAssetLabel::Scene(0).from_asset("Shield.glb")),
-        saucer: asset_server.load(GltfAssetLabel::Scene(0).from_asset("Saucer.v2.glb")),
-        explosion: asset_server.load(GltfAssetLabel::Scene(0).from_asset("Explosion.glb")),
-        missiles: asset_server.load(GltfAssetLabel::Scene(0).from_asset("Bullet.glb")),
-        saucer_missile: asset_server.load(GltfAssetLabel::Scene(0).from_asset("Saucer-Missile.glb")),
-        shooting_sound: asset_server.load("sound/Shoot-2.ogg"),
-        saucer_shooting_sound: asset_server.load("sound/UFO-Lazer-Shoot.ogg"),
-        meteor_hit_sound: asset_server.load("sound/MeteorHit-3.ogg"),
-        thruster_sound: asset_server.load("sound/Rocket.ogg"),
-        shield_ready_sound: asset_server.load("sound/shield_ready.ogg"),
-        saucer_sound: asset_server.load("sound/UFO-Saucer-SFX.ogg"),
-        background_music: asset_server.load("sound/CryForMercyButTheClockTicksDown.ogg"),
-        font: asset_server.load("fonts/fira-sans.bold.ttf"),
+    if scene_assets.is_populated {
+        return;
     }
+
+    let Some(manifest) = manifests.get(&manifest_handle.handle) else {
+        return;
+    };
+
+    *scene_assets = SceneAssets {
+        scenes: manifest.scenes.iter()
+            .map(|(id, path)| (id.clone(), asset_server.load(path.clone())))
+            .collect(),
+        audio: manifest.audio.iter()
+            .map(|(id, path)| (id.clone(), asset_server.load(path.clone())))
+            .collect(),
+        fonts: manifest.fonts.iter()
+            .map(|(id, path)| (id.clone(), asset_server.load(path.clone())))
+            .collect(),
+        is_populated: true,
+        missing: default(),
+    };
 }
@@ -1,95 +1,196 @@
 use std::{
+    f32::consts::PI,
     ops::Range,
-    sync::LazyLock
 };
 
+use serde::{Deserialize, Serialize};
 
+use super::variants::AsteroidVariant;
+use crate::{boss::is_boss_level, config::AsteroidConfig, hazards::HazardKind};
+
+
+// What each asteroid size looks like and where it can turn up.  How it
+// behaves (speed, health, how it splits, ...) is in the GameConfig, so that
+// can be tuned.
 pub struct AsteroidSpawnProperties {
     pub level: usize,
-    pub scene_name: String,
-    pub velocity_scalar: f32,
-    pub acceleration_scalar: f32,
+    pub scene_name: &'static str,
     pub spawn_range_x: Range<f32>,
     pub spawn_range_z: Range<f32>,
-    pub max_rotate_speed: f32,
-    pub radius: f32,
-    pub health: f32,
-    pub collision_damage: f32,
-}
-
-impl AsteroidSpawnProperties {
-    pub fn new(
-        level: usize,
-        scene_name: String,
-        velocity_scalar: f32,
-        acceleration_scalar: f32,
-        spawn_range_x: Range<f32>,
-        spawn_range_z: Range<f32>,
-        max_rotate_speed: f32,
-        radius: f32,
-        health: f32,
-        collision_damage: f32,
-    ) -> Self {
-        Self {
-            level,
-            scene_name,
-            velocity_scalar,
-            acceleration_scalar,
-            spawn_range_x,
-            spawn_range_z,
-            max_rotate_speed,
-            radius,
-            health,
-            collision_damage,
-        }
-    }
 }
 
 
 // This is an array of properties we will use to spawn asteroids at different
 // sizes.  This allows us to split larger asteroids into progressively smaller
 // asteroids.  Right now we have the big, medium, and small asteroid sizes.
-pub static ASTEROID_SIZE_PROPS: [LazyLock<AsteroidSpawnProperties>; 3] = [
-    LazyLock::new(|| {
-        AsteroidSpawnProperties::new(
-            0,  // standard small asteroid
-            String::from("asteroid"),
-            5.0,
-            0.75,
-            -25.0..25.0,
-            -25.0..25.0,
-            3.0,
-            1.5,
-            20.0,
-            35.0,
-        )
-    }),
-    LazyLock::new(|| {
-        AsteroidSpawnProperties::new(
-            1,  // Medium asteroid
-            String::from("asteroid_medium"),
-            5.0,
-            0.5,
-            -25.0..25.0,
-            -25.0..25.0,
-            2.5,
-            3.0,
-            40.0,
-            70.0,
-        )
-    }),
-    LazyLock::new(|| {
-        AsteroidSpawnProperties::new(
-            2,  // Big asteroid
-            String::from("asteroid_big"),
-            5.0,
-            0.5,
-            -25.0..25.0,
-            -25.0..25.0,
-            2.0,
-            4.2,
-            80.0,
-            140.0,
-        )
-    }),
-];
\ No newline at end of file
+pub static ASTEROID_SIZE_PROPS: [AsteroidSpawnProperties; 3] = [
+    AsteroidSpawnProperties {
+        level: 0,  // standard small asteroid
+        scene_name: "asteroid",
+        spawn_range_x: -25.0..25.0,
+        spawn_range_z: -25.0..25.0,
+    },
+    AsteroidSpawnProperties {
+        level: 1,  // Medium asteroid
+        scene_name: "asteroid_medium",
+        spawn_range_x: -25.0..25.0,
+        spawn_range_z: -25.0..25.0,
+    },
+    AsteroidSpawnProperties {
+        level: 2,  // Big asteroid
+        scene_name: "asteroid_big",
+        spawn_range_x: -25.0..25.0,
+        spawn_range_z: -25.0..25.0,
+    },
+];
+
+
+// How an asteroid breaks up when it is destroyed.  Each size has its own in
+// the config, and the fragments are one size smaller.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SplitPattern {
+    pub fragments: usize,
+    pub spread_angle: f32,       // radians, from the first fragment to the last
+    pub speed_inheritance: f32,  // fraction of the parent's velocity
+    pub split_speed: f32,        // extra speed away from the break up
+    pub impact_influence: f32,   // 0.0 ignores the hit, 1.0 follows it exactly
+    pub random_rotation: bool,
+}
+
+// The original break up: two pieces going off at right angles to the way
+// the asteroid was going, whatever hit it.
+const CLASSIC_SPLIT: SplitPattern = SplitPattern {
+    fragments: 2,
+    spread_angle: PI,
+    speed_inheritance: 1.0,
+    split_speed: 2.0,
+    impact_influence: 0.0,
+    random_rotation: false,
+};
+
+// The built-in values for each size, used when the config doesn't say.
+pub const SMALL_ASTEROID: AsteroidConfig = AsteroidConfig {
+    velocity_scalar: 5.0,
+    acceleration_scalar: 0.75,
+    max_rotate_speed: 3.0,
+    radius: 1.5,
+    health: 20.0,
+    collision_damage: 35.0,
+    mass: 1.0,
+    split: SplitPattern {
+        // The small ones just get destroyed.
+        fragments: 0,
+        spread_angle: 0.0,
+        speed_inheritance: 0.0,
+        split_speed: 0.0,
+        impact_influence: 0.0,
+        random_rotation: false,
+    },
+};
+
+pub const MEDIUM_ASTEROID: AsteroidConfig = AsteroidConfig {
+    velocity_scalar: 5.0,
+    acceleration_scalar: 0.5,
+    max_rotate_speed: 2.5,
+    radius: 3.0,
+    health: 40.0,
+    collision_damage: 70.0,
+    mass: 4.0,
+    split: CLASSIC_SPLIT,
+};
+
+pub const BIG_ASTEROID: AsteroidConfig = AsteroidConfig {
+    velocity_scalar: 5.0,
+    acceleration_scalar: 0.5,
+    max_rotate_speed: 2.0,
+    radius: 4.2,
+    health: 80.0,
+    collision_damage: 140.0,
+    mass: 12.0,
+    split: CLASSIC_SPLIT,
+};
+
+
+// What each wave is made up of: the size of every asteroid in it, and how
+// likely each one is to be one of the special variants.
+pub struct WaveDefinition {
+    pub sizes: &'static [usize],
+    pub variant_weights: [(AsteroidVariant, u32); 4],
+    pub hazards: &'static [HazardKind],
+    pub boss: bool,  // the boss takes care of itself, see boss.rs
+}
+
+pub fn wave_definition(level: i32) -> WaveDefinition {
+    if is_boss_level(level) {
+        return WaveDefinition {
+            sizes: &[],
+            variant_weights: [
+                (AsteroidVariant::Normal, 1),
+                (AsteroidVariant::Explosive, 0),
+                (AsteroidVariant::Armored, 0),
+                (AsteroidVariant::OreRich, 0),
+            ],
+            hazards: &[],
+            boss: true,
+        };
+    }
+
+    match level - 1 {
+        0 | 1 => WaveDefinition {
+            sizes: &[0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+            variant_weights: [
+                (AsteroidVariant::Normal, 10),
+                (AsteroidVariant::Explosive, 0),
+                (AsteroidVariant::Armored, 0),
+                (AsteroidVariant::OreRich, 1),
+            ],
+            hazards: &[],
+            boss: false,
+        },
+        2 => WaveDefinition {
+            sizes: &[1, 1, 1, 1],
+            variant_weights: [
+                (AsteroidVariant::Normal, 8),
+                (AsteroidVariant::Explosive, 1),
+                (AsteroidVariant::Armored, 1),
+                (AsteroidVariant::OreRich, 1),
+            ],
+            hazards: &[],
+            boss: false,
+        },
+        3 | 4 => WaveDefinition {
+            sizes: &[1, 1, 1, 1, 0, 0, 0, 0],
+            variant_weights: [
+                (AsteroidVariant::Normal, 6),
+                (AsteroidVariant::Explosive, 2),
+                (AsteroidVariant::Armored, 2),
+                (AsteroidVariant::OreRich, 1),
+            ],
+            hazards: &[HazardKind::GravityWell],
+            boss: false,
+        },
+        5 => WaveDefinition {
+            sizes: &[2, 2],
+            variant_weights: [
+                (AsteroidVariant::Normal, 2),
+                (AsteroidVariant::Explosive, 0),
+                (AsteroidVariant::Armored, 1),
+                (AsteroidVariant::OreRich, 1),
+            ],
+            hazards: &[HazardKind::RepulsorNebula],
+            boss: false,
+        },
+        _ => WaveDefinition {
+            sizes: &[2, 1, 1, 1, 0, 0, 0, 0, 0, 0],
+            variant_weights: [
+                (AsteroidVariant::Normal, 5),
+                (AsteroidVariant::Explosive, 3),
+                (AsteroidVariant::Armored, 3),
+                (AsteroidVariant::OreRich, 1),
+            ],
+            hazards: &[HazardKind::GravityWell, HazardKind::BlackHole],
+            boss: false,
+        },
+    }
+}
+
@@ -1,13 +1,18 @@
-use std::f32::consts::PI;
+use std::f32::consts::TAU;
 
-use bevy::prelude::*;
+use bevy::{ecs::system::SystemParam, prelude::*};
 use rand::Rng;
 
 use crate::{
     app_globals::AppGlobals,
     asset_loader::SceneAssets,
+    boss::Boss,
     collision_detection::{Collider, CollisionDamage},
+    config::{AsteroidConfig, GameConfig},
+    difficulty::{Difficulty, DifficultySettings},
+    director::Director,
     health::Health,
+    lifetime::Lifetime,
     movement::{
         Acceleration,
         Velocity,
@@ -15,30 +20,44 @@ use crate::{
         MovingObjectBundle,
         SceneBundle
     },
+    modes::has_waves,
+    ore::OreWallet,
+    physics::Mass,
     schedule::InGameSet,
-    spaceship::{Spaceship, SPACESHIP_RADIUS},
+    shop::ShopSettings,
+    spaceship::{upgrades::ShipUpgrades, Spaceship, SPACESHIP_RADIUS},
+    state::GameState,
+    waves::{WaveClearedEvent, WaveCountdown},
 };
 
 pub mod levels;
-use levels::ASTEROID_SIZE_PROPS;
+pub mod variants;
+use levels::{wave_definition, AsteroidSpawnProperties, WaveDefinition, ASTEROID_SIZE_PROPS};
+use variants::{roll_variant, Armor, AsteroidVariant, AsteroidVariantPlugin};
 
-// Normal meteor constant values
-const SPAWN_TIME_SECONDS: f32 = 4.0;
+const EXPLOSION_LIFETIME_SECS: f32 = 0.125;
+const DEBRIS_LIFETIME_SECS: f32 = 2.5;
 
 #[derive(Component, Debug)]
 pub struct Asteroid {
-    pub level: usize
+    pub level: usize,
+    pub impact: Vec3,  // velocity of whatever hit it last
+    pub variant: AsteroidVariant,
 }
 
 impl Asteroid {
     pub fn new(level: usize) -> Self {
-        Self { level }
+        Self {
+            level,
+            impact: Vec3::ZERO,
+            variant: AsteroidVariant::Normal,
+        }
     }
 }
 
 #[derive(Resource, Debug)]
 pub struct AsteroidSpawnTimer {
-    timer: Timer,
+    pub timer: Timer,
 }
 
 #[derive(Component, Debug)]
@@ -59,7 +78,7 @@ pub struct AsteroidCollisionAnimationEvent {
 impl AsteroidCollisionAnimationEvent {
     pub fn new(xform: &Transform, velocity: &Velocity, acceleration: &Acceleration) -> Self {
         Self {
-            xform: xform.clone(),
+            xform: *xform,
             velocity: velocity.clone(),
             acceleration: acceleration.clone(),
         }
@@ -73,6 +92,8 @@ pub struct AsteroidSpawnChildrenEvent {
     pub rotation: Rotation,
     pub acceleration: Acceleration,
     pub level: usize,
+    pub impact: Vec3,
+    pub variant: AsteroidVariant,
 }
 
 impl AsteroidSpawnChildrenEvent {
@@ -81,31 +102,77 @@ impl AsteroidSpawnChildrenEvent {
         velocity: &Velocity,
         rotation: &Rotation,
         acceleration: &Acceleration,
-        level: usize
+        level: usize,
+        impact: Vec3,
+        variant: AsteroidVariant,
     ) -> Self {
         Self {
-            xform: xform.clone(),
+            xform: *xform,
             velocity: velocity.clone(),
             rotation: rotation.clone(),
             acceleration: acceleration.clone(),
-            level
+            level,
+            impact,
+            variant,
         }
     }
 }
 
+// Sent whenever a new wave of asteroids is launched.
+#[derive(Message, Debug)]
+pub struct WaveStartedEvent {
+    pub level: i32,
+}
+
+// What it takes to put new asteroids out there, and how lively to make them.
+#[derive(SystemParam)]
+pub struct WaveSetup<'w> {
+    pub scene_assets: Res<'w, SceneAssets>,
+    pub difficulty: Res<'w, Difficulty>,
+    pub director: Res<'w, Director>,
+    pub config: Res<'w, GameConfig>,
+}
+
+// What is still out there from the last wave.
+#[derive(SystemParam)]
+struct WaveField<'w, 's> {
+    ships: Query<'w, 's, (&'static Transform, &'static ShipUpgrades), With<Spaceship>>,
+    asteroids: Query<'w, 's, Entity, With<Asteroid>>,
+    bosses: Query<'w, 's, (), With<Boss>>,
+}
+
+// Keeping track of where we are between waves, and telling everyone else.
+#[derive(SystemParam)]
+struct WaveProgress<'w> {
+    spawn_timer: ResMut<'w, AsteroidSpawnTimer>,
+    countdown: ResMut<'w, WaveCountdown>,
+    wave_started_writer: MessageWriter<'w, WaveStartedEvent>,
+    wave_cleared_writer: MessageWriter<'w, WaveClearedEvent>,
+}
+
+// The upgrade shop, which may open before the next wave.
+#[derive(SystemParam)]
+struct ShopEntrance<'w> {
+    shop: ResMut<'w, ShopSettings>,
+    wallet: Res<'w, OreWallet>,
+    next_state: ResMut<'w, NextState<GameState>>,
+}
+
 
 pub struct AsteroidPlugin;
 
 impl Plugin for AsteroidPlugin {
     fn build(&self, app: &mut App) {
-        app.insert_resource(AsteroidSpawnTimer {
+        app.add_plugins(AsteroidVariantPlugin)
+        .insert_resource(AsteroidSpawnTimer {
+            // The difficulty sets the real duration.
             timer: Timer::from_seconds(
-                SPAWN_TIME_SECONDS,
+                DifficultySettings::default().asteroid_spawn_secs,
                 TimerMode::Repeating
             )
         })
         .add_systems(Update, (
-                spawn_asteroids,
+                spawn_asteroids.run_if(has_waves),
                 rotate_passive_objects::<Asteroid>,
                 rotate_passive_objects::<AsteroidDebris>,
                 spawn_collision_animation,
@@ -114,91 +181,127 @@ impl Plugin for AsteroidPlugin {
             ).in_set(InGameSet::EntityUpdates),
         )
         .add_message::<AsteroidCollisionAnimationEvent>()
-        .add_message::<AsteroidSpawnChildrenEvent>();
+        .add_message::<AsteroidSpawnChildrenEvent>()
+        .add_message::<WaveStartedEvent>();
     }
 }
 
 fn spawn_asteroids(
     mut commands: Commands,
-    spaceship_xform: Single<&Transform, With<Spaceship>>,
-    asteroids: Query<Entity, With<Asteroid>>,
-    scene_assets: Res<SceneAssets>,
-    mut spawn_timer: ResMut<AsteroidSpawnTimer>,
+    field: WaveField,
+    setup: WaveSetup,
+    progress: WaveProgress,
+    entrance: ShopEntrance,
     mut app_globals: ResMut<AppGlobals>,
     time: Res<Time>,
 ) {
+    let WaveField { ships, asteroids, bosses } = field;
+    let WaveProgress {
+        mut spawn_timer,
+        mut countdown,
+        mut wave_started_writer,
+        mut wave_cleared_writer,
+    } = progress;
+    let ShopEntrance { mut shop, wallet, mut next_state } = entrance;
+
     // We are setting up a game dynamic where a wave of asteroids, up to
     // about 10 or so, is spawned all at once.  Enough that it is challanging,
     // but not impossible.
     // When the player is done shooting the last asteroid, we will spawn
     // another wave.
+    if countdown.active {
+        // The "Wave N" banner is up.  Launch the wave once it counts down.
+        if !countdown.timer.tick(time.delta()).just_finished() {
+            return;
+        }
+
+        countdown.active = false;
+        countdown.cleared = false;
+
+        // New level.
+        //#[cfg(debug_assertions)]
+        info!("New level: {:}", app_globals.level);
+        
+        let wave = wave_definition(app_globals.level);
+
+        let ship_positions = ships.iter().map(|(xform, _)| xform.translation).collect::<Vec<_>>();
+        spawn_new_wave(&wave, &mut commands, &ship_positions, &setup);
+        wave_started_writer.write(WaveStartedEvent { level: app_globals.level });
+
+        app_globals.level += 1;
+        return;
+    }
+
     spawn_timer.timer.tick(time.delta());
     if !spawn_timer.timer.just_finished() {
         return;
     }
 
-    if asteroids.iter().len() == 0 {
-        // All asteroids have been cleared.  New level.
-        //#[cfg(debug_assertions)]
-        info!("New level: {:}", app_globals.level);
-        
-        let mut m_sizes: Vec<usize> = Vec::new();
-
-        match app_globals.level - 1 {
-            0 | 1 => m_sizes.extend([0, 0, 0, 0, 0, 0, 0, 0, 0, 0].iter()),
-            2 => m_sizes.extend([1, 1, 1, 1].iter()),
-            3 | 4 => m_sizes.extend([1, 1, 1, 1, 0, 0, 0, 0].iter()),
-            5 => m_sizes.extend([2, 2].iter()),
-            _ => m_sizes.extend([2, 1, 1, 1, 0, 0, 0, 0, 0, 0].iter()),
+    if asteroids.iter().len() == 0 && bosses.is_empty() {
+        // All asteroids (or the boss) have been cleared.  Show how the
+        // player did, and leave it up for a bit before moving on.
+        if !countdown.cleared {
+            countdown.cleared = true;
+
+            if app_globals.level > 1 {
+                wave_cleared_writer.write(WaveClearedEvent { level: app_globals.level - 1 });
+                return;
+            }
         }
 
-        spawn_new_wave(&m_sizes, &mut commands, spaceship_xform, scene_assets);
+        // Give the player a chance to spend their ore; the countdown
+        // starts when they come back.  Upgrades are shared, so any ship
+        // will do to see what they can afford.
+        if let Some((_, ship_upgrades)) = ships.iter().next() {
+            if shop.open_before_wave(app_globals.level, &wallet, ship_upgrades, &mut next_state) {
+                return;
+            }
+        }
 
-        app_globals.level += 1;
+        countdown.start();
     }
 }
 
 
 fn spawn_new_wave(
-    meteor_sizes: &[usize],
-    mut commands: &mut Commands,
-    spaceship_xform: Single<&Transform, With<Spaceship>>,
-    scene_assets: Res<SceneAssets>,
+    wave: &WaveDefinition,
+    commands: &mut Commands,
+    ship_positions: &[Vec3],
+    setup: &WaveSetup,
 ) {
-    for meteor_size in meteor_sizes {
+    let mut rng = rand::rng();
+
+    for meteor_size in wave.sizes {
         spawn_random_asteroid(
-            &mut commands,
-            &spaceship_xform,
-            &scene_assets,
+            commands,
+            ship_positions,
+            setup,
             *meteor_size,
+            roll_variant(&wave.variant_weights, &mut rng),
         );
     }
 }
 
 
-fn spawn_random_asteroid(
+pub fn spawn_random_asteroid(
     commands: &mut Commands,
-    spaceship_xform: &Transform,
-    scenes: &Res<SceneAssets>,
+    ship_positions: &[Vec3],
+    setup: &WaveSetup,
     mut level: usize,
+    variant: AsteroidVariant,
 ) {
     // Big meteor constant values
     if level > 2 {
         level = 2;
     }
 
+    let speed_scalar = setup.director.asteroid_speed;
+    let acceleration_scalar = setup.difficulty.settings().asteroid_acceleration_scalar * speed_scalar;
+
     let spawn_props = &ASTEROID_SIZE_PROPS[level];
+    let tunables = setup.config.asteroids.size(level);
 
-    let scene: Handle<Scene>;
-    if level >= 2 {
-        scene = scenes.asteroid_big.clone();
-    }
-    else if level >= 1 {
-        scene = scenes.asteroid_medium.clone();
-    }
-    else {
-        scene = scenes.asteroid.clone();
-    }
+    let scene = setup.scene_assets.scene(spawn_props.scene_name);
 
     let mut rng = rand::rng();
 
@@ -209,15 +312,17 @@ fn spawn_random_asteroid(
     );
 
     for _i in 0..4 {
-        // It is a bit unfair to have an asteroid spawn right on top of the
+        // It is a bit unfair to have an asteroid spawn right on top of a
         // spaceship.  So we allow a (finite) number of chances to choose
         // a different location if this happens.
         // There is still a tiny chance of this happening, but it will be
         // considerably less annoying.  Without this, it was happening
         // at least once per game.
-        let distance = translation.distance(spaceship_xform.translation);
+        let too_close = ship_positions
+            .iter()
+            .any(|ship| translation.distance(*ship) < SPACESHIP_RADIUS * 4.0);
 
-        if distance >= SPACESHIP_RADIUS * 4.0 {
+        if !too_close {
             break;
         }
         else {
@@ -230,8 +335,8 @@ fn spawn_random_asteroid(
     }
 
     let rotation = Rotation::random(
-        -spawn_props.max_rotate_speed,
-        spawn_props.max_rotate_speed
+        -tunables.max_rotate_speed,
+        tunables.max_rotate_speed
     );
 
     let mut random_unit_vector = 
@@ -241,29 +346,74 @@ fn spawn_random_asteroid(
             rng.random_range(-1.0..1.0)
         ).normalize_or_zero();
 
-    let velocity = random_unit_vector() * spawn_props.velocity_scalar;
+    let velocity = random_unit_vector() * tunables.velocity_scalar * speed_scalar;
 
     // Technically, an asteroid would not have any significant acceleration,
     // But having it gradually speed up over time adds some challenge to the
     // gameplay.
-    let acceleration = random_unit_vector() * spawn_props.acceleration_scalar;
+    let acceleration = random_unit_vector() * tunables.acceleration_scalar * acceleration_scalar;
+
+    spawn_asteroid(
+        commands,
+        scene,
+        spawn_props,
+        tunables,
+        variant,
+        AsteroidMotion {
+            velocity: Velocity { value: velocity },
+            acceleration: Acceleration { value: acceleration },
+            rotation,
+            transform: Transform::from_translation(translation),
+        },
+    );
+}
+
+// Where a new asteroid starts out and how it is moving.
+struct AsteroidMotion {
+    velocity: Velocity,
+    acceleration: Acceleration,
+    rotation: Rotation,
+    transform: Transform,
+}
 
-    commands.spawn((
+// Every asteroid, new or a fragment of an old one, comes through here, so
+// its variant gets the same health, armor and (via the variant plugin) tint
+// whichever way it got here.
+fn spawn_asteroid(
+    commands: &mut Commands,
+    scene: Handle<Scene>,
+    spawn_props: &AsteroidSpawnProperties,
+    tunables: &AsteroidConfig,
+    variant: AsteroidVariant,
+    motion: AsteroidMotion,
+) {
+    let AsteroidMotion { velocity, acceleration, rotation, transform } = motion;
+    let health = tunables.health * variant.props().health_scalar;
+
+    let mut asteroid = commands.spawn((
         Name::new("asteroid"),
         MovingObjectBundle {
-            velocity: Velocity { value: velocity },
-            acceleration: Acceleration { value: acceleration },
-            rotation: rotation,
-            collider: Collider::new(spawn_props.radius),
+            velocity,
+            acceleration,
+            rotation,
+            collider: Collider::new(tunables.radius),
             model: SceneBundle {
                 scene: SceneRoot(scene),
-                transform: Transform::from_translation(translation),
+                transform,
             },
         },
-        Asteroid::new(spawn_props.level),
-        Health::new(spawn_props.health),
-        CollisionDamage::new(spawn_props.collision_damage),
+        Asteroid {
+            variant,
+            ..Asteroid::new(spawn_props.level)
+        },
+        Health::new(health),
+        CollisionDamage::new(tunables.collision_damage),
+        Mass::new(tunables.mass),
     ));
+
+    if variant == AsteroidVariant::Armored {
+        asteroid.insert(Armor::default());
+    }
 }
 
 fn rotate_passive_objects<T: Component>(
@@ -280,8 +430,9 @@ fn spawn_collision_animation(
     mut animation_event_reader: MessageReader<AsteroidCollisionAnimationEvent>,
     mut commands: Commands,
     scene_assets: Res<SceneAssets>,
+    config: Res<GameConfig>,
 ) {
-    let spawn_props = &ASTEROID_SIZE_PROPS[0];
+    let tunables = config.asteroids.size(0);
 
     for &AsteroidCollisionAnimationEvent {
         xform,
@@ -290,31 +441,31 @@ fn spawn_collision_animation(
     } in animation_event_reader.read() {
         let mut rng = rand::rng();
 
-        let mut debris_velocity = Velocity::from(velocity.clone());
+        let mut debris_velocity = velocity.clone();
         debris_velocity.value *= rng.random_range(0.6..1.0);
 
         let mut debris_xform = Transform::from_translation(xform.translation);
         debris_xform.scale *= rng.random_range(0.1..0.4);
 
         let rotation = Rotation::random(
-            -spawn_props.max_rotate_speed / 2.0,
-            spawn_props.max_rotate_speed / 2.0,
+            -tunables.max_rotate_speed / 2.0,
+            tunables.max_rotate_speed / 2.0,
         );
 
         commands.spawn((
             Name::new("explosion"),
             MovingObjectBundle {
                 velocity: velocity.clone(),
-                acceleration: Acceleration::from(acceleration.clone()),
+                acceleration: acceleration.clone(),
                 rotation: rotation.clone(),
-                collider: Collider::new(spawn_props.radius),
+                collider: Collider::new(tunables.radius),
                 model: SceneBundle {
-                    scene: SceneRoot(scene_assets.explosion.clone()),
+                    scene: SceneRoot(scene_assets.scene("explosion")),
                     transform: debris_xform,
                 },
             },
-            Health::new(spawn_props.health),
             Explosion {duration: 0},
+            Lifetime::new(EXPLOSION_LIFETIME_SECS),
         ));
 
         commands.spawn((
@@ -323,25 +474,25 @@ fn spawn_collision_animation(
                 velocity: debris_velocity.clone(),
                 acceleration: Acceleration::new(acceleration.value * 0.1),
                 rotation: rotation.clone(),
-                collider: Collider::new(spawn_props.radius),
+                collider: Collider::new(tunables.radius),
                 model: SceneBundle {
-                    scene: SceneRoot(scene_assets.asteroid_debris.clone()),
+                    scene: SceneRoot(scene_assets.scene("asteroid_debris")),
                     transform: debris_xform,
                 },
             },
             AsteroidDebris,
+            Lifetime::new(DEBRIS_LIFETIME_SECS),
         ));
 
     }
 }
 
 fn update_explosion_animation(
-    mut query: Query<(&mut Explosion, &mut Health, &mut Transform), With<Explosion>>,
+    mut query: Query<(&mut Explosion, &mut Transform)>,
     time: Res<Time>,
 ) {
-    for (mut explosion, mut health, mut xform) in query.iter_mut() {
+    for (mut explosion, mut xform) in query.iter_mut() {
         explosion.duration += 1;
-        health.value -= 160.0 * time.delta_secs();
         xform.scale *= 1.0 + (12.0 * time.delta_secs());
     }
 }
@@ -349,82 +500,87 @@ fn update_explosion_animation(
 fn split_asteroid(
     mut commands: Commands,
     scene_assets: Res<SceneAssets>,
+    config: Res<GameConfig>,
     mut event_reader: MessageReader<AsteroidSpawnChildrenEvent>,
 ) {
+    let mut rng = rand::rng();
+
     for &AsteroidSpawnChildrenEvent {
         ref xform,
         ref velocity,
         ref rotation,
         ref acceleration,
-        level
+        level,
+        impact,
+        ..
     } in event_reader.read() {
-        if level >= 1 {
-            let new_level = if level >= 1 {level - 1} else {level};
-
-            let spawn_props = &ASTEROID_SIZE_PROPS[new_level];
-
-            let scene: Handle<Scene>;
+        let pattern = &config.asteroids.size(level).split;
+        if level == 0 || pattern.fragments == 0 {
+            continue;
+        }
 
-            if new_level >= 2 {
-                scene = scene_assets.asteroid_big.clone();
-            }
-            else if new_level >= 1 {
-                scene = scene_assets.asteroid_medium.clone();
+        let new_level = level - 1;
+        let spawn_props = &ASTEROID_SIZE_PROPS[new_level];
+        let tunables = config.asteroids.size(new_level);
+        let scene = scene_assets.scene(spawn_props.scene_name);
+
+        // The fragments fan out around the way the asteroid was going,
+        // pulled round towards the way the missile was going when it hit.
+        let heading = velocity.value.normalize_or(Vec3::Z);
+        let heading = match impact.try_normalize() {
+            Some(impact) => heading
+                .lerp(impact, pattern.impact_influence)
+                .normalize_or(heading),
+            None => heading,
+        };
+
+        for i in 0..pattern.fragments {
+            let angle = if pattern.fragments > 1 {
+                pattern.spread_angle * (i as f32 / (pattern.fragments - 1) as f32 - 0.5)
             }
             else {
-                scene = scene_assets.asteroid.clone();
-            }
+                0.0
+            };
+            let direction = heading.rotate_y(angle);
 
-            let new_velocity_vec = velocity.value.rotate_y(PI / 2.0).normalize();
-            let new_left_velocity = Velocity::new(
-                velocity.value + (new_velocity_vec * 2.0)
+            let new_velocity = Velocity::new(
+                velocity.value * pattern.speed_inheritance + direction * pattern.split_speed
             );
-            let new_left_acceleration = Acceleration::new(
-                new_left_velocity.value.normalize() * acceleration.value.length()
+            let new_acceleration = Acceleration::new(
+                new_velocity.value.normalize_or_zero() * acceleration.value.length()
             );
 
-            let new_right_velocity = Velocity::new(
-                velocity.value - (new_velocity_vec * 2.0)
-            );
-            let new_right_acceleration = Acceleration::new(
-                new_right_velocity.value.normalize() * acceleration.value.length()
-            );
+            let new_rotation = if pattern.random_rotation {
+                Rotation::random(-tunables.max_rotate_speed, tunables.max_rotate_speed)
+            }
+            else {
+                rotation.clone()
+            };
+
+            // Start them a little apart so they aren't all on top of each
+            // other on the first frame.
+            let mut new_xform = *xform;
+            new_xform.translation += direction * tunables.radius * 0.5;
+            if pattern.random_rotation {
+                new_xform.rotation = Quat::from_rotation_y(rng.random_range(0.0..TAU));
+            }
 
-            commands.spawn((
-                Name::new("asteroid"),
-                MovingObjectBundle {
-                    velocity: new_left_velocity,
-                    acceleration: new_left_acceleration,
-                    rotation: rotation.clone(),
-                    collider: Collider::new(spawn_props.radius),
-                    model: SceneBundle {
-                        scene: SceneRoot(scene.clone()),
-                        transform: *xform,
-                    },
+            // The fragments are always plain rock, otherwise an armored
+            // asteroid would split into more armored ones and an explosive
+            // one would set off a chain of explosions.
+            spawn_asteroid(
+                &mut commands,
+                scene.clone(),
+                spawn_props,
+                tunables,
+                AsteroidVariant::Normal,
+                AsteroidMotion {
+                    velocity: new_velocity,
+                    acceleration: new_acceleration,
+                    rotation: new_rotation,
+                    transform: new_xform,
                 },
-                Asteroid::new(new_level),
-                Health::new(spawn_props.health),
-                CollisionDamage::new(spawn_props.collision_damage),
-            ));
-
-            commands.spawn((
-                Name::new("asteroid"),
-                MovingObjectBundle {
-                    velocity: new_right_velocity,
-                    acceleration: new_right_acceleration,
-                    rotation: rotation.clone(),
-                    collider: Collider::new(spawn_props.radius),
-                    model: SceneBundle {
-                        scene: SceneRoot(scene.clone()),
-                        transform: *xform,
-                    },
-                },
-                Asteroid::new(new_level),
-                Health::new(spawn_props.health),
-                CollisionDamage::new(spawn_props.collision_damage),
-            ));
-
+            );
         }
-
     }
 }
@@ -0,0 +1,304 @@
+use std::f32::consts::FRAC_PI_2;
+
+use bevy::prelude::*;
+use rand::Rng;
+
+use crate::{
+    health::Health,
+    lifetime::Lifetime,
+    schedule::InGameSet,
+    sound::effects::AsteroidCollisionSoundEvent,
+    spaceship::{hyperspace::Warping, shield::Shield},
+};
+use super::{
+    Asteroid,
+    AsteroidCollisionAnimationEvent,
+    AsteroidSpawnChildrenEvent,
+};
+
+const EXPLOSION_RADIUS: f32 = 12.0;
+const EXPLOSION_DAMAGE: f32 = 60.0;  // right at the centre, less further out
+const SHOCKWAVE_SECS: f32 = 0.3;
+const SHOCKWAVE_COLOR: Color = Color::srgb(1.0, 0.5, 0.1);
+
+const ARMOR_FLASH_SECS: f32 = 0.15;
+const ARMOR_FLASH_EMISSIVE: f32 = 6.0;
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AsteroidVariant {
+    Normal,
+    Explosive,
+    Armored,
+    OreRich,
+}
+
+impl AsteroidVariant {
+    pub fn props(&self) -> &'static AsteroidVariantProperties {
+        match self {
+            AsteroidVariant::Normal => &NORMAL,
+            AsteroidVariant::Explosive => &EXPLOSIVE,
+            AsteroidVariant::Armored => &ARMORED,
+            AsteroidVariant::OreRich => &ORE_RICH,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct AsteroidVariantProperties {
+    pub tint: Option<Color>,  // None keeps the model's own colours
+    pub glow: f32,  // emissive strength of the tint
+    pub health_scalar: f32,
+    pub points: i32,
+    pub ore_scalar: u32,  // multiplies the ore it breaks up into
+}
+
+const NORMAL: AsteroidVariantProperties = AsteroidVariantProperties {
+    tint: None,
+    glow: 0.0,
+    health_scalar: 1.0,
+    points: 1,
+    ore_scalar: 1,
+};
+
+// Blows up when destroyed, damaging anything close by (including you).
+const EXPLOSIVE: AsteroidVariantProperties = AsteroidVariantProperties {
+    tint: Some(Color::srgb(1.0, 0.45, 0.3)),
+    glow: 0.4,
+    health_scalar: 1.0,
+    points: 2,
+    ore_scalar: 1,
+};
+
+// Takes several hits to break, and flashes every time it is hit so you
+// know it is working.
+const ARMORED: AsteroidVariantProperties = AsteroidVariantProperties {
+    tint: Some(Color::srgb(0.55, 0.6, 0.7)),
+    glow: 0.0,
+    health_scalar: 3.0,
+    points: 3,
+    ore_scalar: 1,
+};
+
+const ORE_RICH: AsteroidVariantProperties = AsteroidVariantProperties {
+    tint: Some(Color::srgb(1.0, 0.85, 0.3)),
+    glow: 1.5,
+    health_scalar: 1.0,
+    points: 5,
+    ore_scalar: 3,
+};
+
+
+// The tinted copies of an asteroid's materials, so we don't tint every
+// other asteroid that shares the model.
+#[derive(Component, Debug)]
+pub struct VariantMaterials {
+    pub handles: Vec<Handle<StandardMaterial>>,
+}
+
+#[derive(Component, Debug)]
+pub struct Armor {
+    pub flash: Timer,
+}
+
+impl Default for Armor {
+    fn default() -> Self {
+        let mut flash = Timer::from_seconds(ARMOR_FLASH_SECS, TimerMode::Once);
+        flash.set_elapsed(flash.duration());  // start out not flashing
+
+        Self { flash }
+    }
+}
+
+impl Armor {
+    // Everything that damages an armored rock goes through here, missiles
+    // and blasts alike, so it flashes however it got hit.
+    pub fn hit(&mut self, health: &mut Health, amount: f32) {
+        health.value -= amount;
+        self.flash.reset();
+    }
+}
+
+#[derive(Component, Debug)]
+pub struct Shockwave {
+    pub center: Vec3,
+}
+
+
+pub struct AsteroidVariantPlugin;
+
+impl Plugin for AsteroidVariantPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, (
+                tint_variant_asteroids,
+                flash_armored_asteroids,
+                explode_asteroids,
+                draw_shockwaves,
+            ).in_set(InGameSet::EntityUpdates),
+        );
+    }
+}
+
+// Picks a variant from a wave's spawn weights.
+pub fn roll_variant(weights: &[(AsteroidVariant, u32)], rng: &mut impl Rng) -> AsteroidVariant {
+    let total: u32 = weights.iter().map(|(_, w)| w).sum();
+    if total == 0 {
+        return AsteroidVariant::Normal;
+    }
+
+    let mut pick = rng.random_range(0..total);
+
+    for (variant, weight) in weights.iter() {
+        if pick < *weight {
+            return *variant;
+        }
+        pick -= weight;
+    }
+
+    AsteroidVariant::Normal
+}
+
+fn tint_variant_asteroids(
+    mut commands: Commands,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    asteroids: Query<(Entity, &Asteroid), Without<VariantMaterials>>,
+    children_q: Query<&Children>,
+    mut mat_q: Query<&mut MeshMaterial3d<StandardMaterial>>,
+) {
+    for (entity, asteroid) in asteroids.iter() {
+        let props = asteroid.variant.props();
+        let Some(tint) = props.tint else {
+            continue;
+        };
+
+        let mut handles = vec![];
+
+        for child in children_q.iter_descendants(entity) {
+            let Ok(mut mat3d) = mat_q.get_mut(child) else {
+                continue;
+            };
+            let Some(mut new_mat) = materials.get(mat3d.0.id()).cloned() else {
+                continue;
+            };
+
+            new_mat.base_color = tint;
+            new_mat.emissive = tint.to_linear() * props.glow;
+
+            let handle = materials.add(new_mat);
+            mat3d.0 = handle.clone();
+            handles.push(handle);
+        }
+
+        // The scene might not have finished spawning yet, in which case we
+        // try again next frame.
+        if !handles.is_empty() {
+            commands.entity(entity).insert(VariantMaterials { handles });
+        }
+    }
+}
+
+fn flash_armored_asteroids(
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut query: Query<(&Asteroid, &mut Armor, Option<&VariantMaterials>)>,
+    time: Res<Time>,
+) {
+    for (asteroid, mut armor, variant_materials) in query.iter_mut() {
+        let was_flashing = !armor.flash.is_finished();
+        armor.flash.tick(time.delta());
+        if !was_flashing {
+            continue;
+        }
+
+        let Some(variant_materials) = variant_materials else {
+            continue;
+        };
+
+        let props = asteroid.variant.props();
+        let tint = props.tint.unwrap_or(Color::WHITE).to_linear();
+        let flash = 1.0 - armor.flash.fraction();
+
+        for handle in variant_materials.handles.iter() {
+            if let Some(mat) = materials.get_mut(handle.id()) {
+                mat.emissive = tint * (props.glow + ARMOR_FLASH_EMISSIVE * flash);
+            }
+        }
+    }
+}
+
+// Whatever a blast can hurt directly.  Ships mid-jump are out of reach,
+// and a shield only takes the damage its ship would have.
+type BlastTarget = (Without<Warping>, Without<Shield>);
+
+fn explode_asteroids(
+    mut commands: Commands,
+    mut event_reader: MessageReader<AsteroidSpawnChildrenEvent>,
+    mut targets: Query<(Entity, &Transform, &mut Health, Option<&mut Armor>), BlastTarget>,
+    mut shields: Query<(&Shield, &mut Health)>,
+    mut animation_event_writer: MessageWriter<AsteroidCollisionAnimationEvent>,
+    mut sound_event_writer: MessageWriter<AsteroidCollisionSoundEvent>,
+) {
+    for event in event_reader.read() {
+        if event.variant != AsteroidVariant::Explosive {
+            continue;
+        }
+
+        let center = event.xform.translation;
+
+        for (entity, xform, mut health, armor) in targets.iter_mut() {
+            let distance = xform.translation.distance(center);
+            if distance >= EXPLOSION_RADIUS || health.value <= 0.0 {
+                continue;
+            }
+
+            let damage = EXPLOSION_DAMAGE * (1.0 - distance / EXPLOSION_RADIUS);
+
+            // A raised shield takes the hull's share instead.
+            if let Some((_, mut shield_health)) = shields
+                .iter_mut()
+                .find(|(shield, _)| shield.ship == entity)
+            {
+                shield_health.value -= damage;
+                continue;
+            }
+
+            match armor {
+                Some(mut armor) => armor.hit(&mut health, damage),
+                None => health.value -= damage,
+            }
+        }
+
+        animation_event_writer.write(AsteroidCollisionAnimationEvent::new(
+            &event.xform,
+            &event.velocity,
+            &event.acceleration,
+        ));
+        sound_event_writer.write(AsteroidCollisionSoundEvent);
+
+        commands.spawn((
+            Name::new("shockwave"),
+            Shockwave { center },
+            Lifetime::new(SHOCKWAVE_SECS),
+        ));
+
+        #[cfg(debug_assertions)]
+        info!("Explosive asteroid went off at {:?}", center);
+    }
+}
+
+fn draw_shockwaves(
+    mut gizmos: Gizmos,
+    query: Query<(&Shockwave, &Lifetime)>,
+) {
+    let flat = Quat::from_rotation_x(FRAC_PI_2);
+
+    for (shockwave, lifetime) in query.iter() {
+        let t = lifetime.timer.fraction();
+        let color = SHOCKWAVE_COLOR.with_alpha(1.0 - t);
+
+        gizmos.circle(
+            Isometry3d::new(shockwave.center, flat),
+            (EXPLOSION_RADIUS * t).max(0.05),
+            color,
+        );
+    }
+}
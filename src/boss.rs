@@ -0,0 +1,448 @@
+use std::f32::consts::{PI, TAU};
+
+use bevy::prelude::*;
+
+use crate::{
+    asset_loader::SceneAssets,
+    asteroids::{levels::wave_definition, AsteroidCollisionAnimationEvent, WaveStartedEvent},
+    collision_detection::{Collider, CollisionDamage},
+    health::Health,
+    movement::{play_area_bounds, Acceleration, Velocity},
+    saucer::{
+        aim::{aim_direction, SaucerAimMode},
+        MissileSpawner,
+    },
+    players::{LastHitBy, Player, TeamScore},
+    schedule::InGameSet,
+    sound::effects::{AsteroidCollisionSoundEvent, SaucerShootingSoundEvent},
+    spaceship::{nearest_ship, Spaceship},
+    state::GameState,
+};
+
+// Every this many levels, the wave is a boss instead of asteroids.
+pub const BOSS_EVERY_N_LEVELS: i32 = 5;
+
+const BOSS_POINTS: i32 = 1000;
+const BOSS_SIZE: f32 = 2.2;
+const BOSS_COLLISION_DAMAGE: f32 = 50.0;
+
+// It patrols back and forth across the top of the screen.
+const BOSS_PATROL_MARGIN: f32 = 15.0;
+const BOSS_PATROL_SECS: f32 = 8.0;  // for one full sweep there and back
+const BOSS_STEERING: f32 = 2.0;
+
+const TURRET_HEALTH: f32 = 100.0;
+const TURRET_RADIUS: f32 = 1.8;
+const TURRET_OFFSET: Vec3 = Vec3::new(6.0, 0.0, 0.0);  // either side of the core
+
+const CORE_HEALTH: f32 = 300.0;
+const CORE_RADIUS: f32 = 2.8;
+const CORE_ENRAGED_FRACTION: f32 = 0.4;  // core health that sets it off
+
+const CORE_FAN_MISSILES: usize = 5;
+const CORE_FAN_ANGLE: f32 = 0.2;  // radians between fan missiles
+const ENRAGED_RING_MISSILES: usize = 12;
+
+const BOSS_ARMORED_COLOR: Color = Color::srgb(0.4, 0.4, 0.45);
+const BOSS_EXPOSED_COLOR: Color = Color::srgb(1.0, 0.2, 0.2);
+const BOSS_TURRET_COLOR: Color = Color::srgb(1.0, 0.6, 0.1);
+
+
+// The boss goes through its phases as it gets shot up.  The core is
+// armored until both turrets are gone, then it starts fighting back, and
+// it gets really angry once it is badly hurt.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BossPhase {
+    Turrets,
+    Core,
+    Enraged,
+}
+
+impl BossPhase {
+    fn attack_secs(&self) -> f32 {
+        match self {
+            BossPhase::Turrets => 1.5,
+            BossPhase::Core => 2.0,
+            BossPhase::Enraged => 1.2,
+        }
+    }
+
+    fn patrol_speed(&self) -> f32 {
+        match self {
+            BossPhase::Turrets => 6.0,
+            BossPhase::Core => 9.0,
+            BossPhase::Enraged => 14.0,
+        }
+    }
+}
+
+#[derive(Component, Debug)]
+pub struct Boss {
+    pub phase: BossPhase,
+    pub attack: Timer,
+    pub patrol_time: f32,
+    pub max_health: f32,  // of all the hit zones together
+    pub last_hit_by: Option<Player>,  // on any of the hit zones
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HitZoneKind {
+    Turret,
+    Core,
+}
+
+// The bits of the boss that can be shot.  These are separate entities
+// rather than children, because the collision detection works in world
+// space, so they get moved along with the boss every frame instead.
+#[derive(Component, Debug)]
+pub struct BossHitZone {
+    pub boss: Entity,
+    pub kind: HitZoneKind,
+    pub offset: Vec3,
+    pub max_health: f32,
+}
+
+#[derive(Resource, Debug)]
+pub struct BossAssets {
+    mesh: Handle<Mesh>,
+    armored: Handle<StandardMaterial>,
+    exposed: Handle<StandardMaterial>,
+    turret: Handle<StandardMaterial>,
+}
+
+
+pub struct BossPlugin;
+
+impl Plugin for BossPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, create_boss_assets)
+        .add_systems(Update, (
+                spawn_boss,
+                boss_movement,
+                hit_zones_follow_boss,
+                update_boss_phase,
+                boss_attacks,
+                boss_defeated,
+            )
+            .chain()
+            .in_set(InGameSet::EntityUpdates),
+        )
+        // Before the DespawnPlugin gets rid of the zone that took the last hit.
+        .add_systems(Update,
+            remember_last_hit.in_set(InGameSet::DespawnEntities),
+        )
+        .add_systems(OnEnter(GameState::GameOver), despawn_boss);
+    }
+}
+
+pub fn is_boss_level(level: i32) -> bool {
+    level % BOSS_EVERY_N_LEVELS == 0
+}
+
+fn create_boss_assets(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    let mut glow = |color: Color, strength: f32| materials.add(StandardMaterial {
+        base_color: color,
+        emissive: color.to_linear() * strength,
+        ..default()
+    });
+
+    commands.insert_resource(BossAssets {
+        mesh: meshes.add(Sphere::new(1.0)),
+        armored: glow(BOSS_ARMORED_COLOR, 0.0),
+        exposed: glow(BOSS_EXPOSED_COLOR, 4.0),
+        turret: glow(BOSS_TURRET_COLOR, 2.0),
+    });
+}
+
+fn spawn_boss(
+    mut commands: Commands,
+    mut wave_started_reader: MessageReader<WaveStartedEvent>,
+    scene_assets: Res<SceneAssets>,
+    boss_assets: Res<BossAssets>,
+    camera_query: Query<&Projection, With<Camera>>,
+) {
+    for event in wave_started_reader.read() {
+        if !wave_definition(event.level).boss {
+            continue;
+        }
+
+        let Ok(projection) = camera_query.single() else { return; };
+        let (_, max) = play_area_bounds(projection);
+        let translation = Vec3::new(0.0, 0.0, max.y - BOSS_PATROL_MARGIN);
+
+        let phase = BossPhase::Turrets;
+        let boss = commands.spawn((
+            Name::new("boss"),
+            Boss {
+                phase,
+                attack: Timer::from_seconds(phase.attack_secs(), TimerMode::Repeating),
+                patrol_time: 0.0,
+                max_health: TURRET_HEALTH * 2.0 + CORE_HEALTH,
+                last_hit_by: None,
+            },
+            Velocity::new(Vec3::ZERO),
+            Acceleration::new(Vec3::ZERO),
+            SceneRoot(scene_assets.scene("saucer")),
+            Transform::from_translation(translation)
+                .with_scale(Vec3::ONE * BOSS_SIZE)
+                .with_rotation(Quat::from_rotation_x(PI / 2.)),
+        )).id();
+
+        for offset in [-TURRET_OFFSET, TURRET_OFFSET] {
+            commands.spawn((
+                Name::new("boss_turret"),
+                BossHitZone {
+                    boss,
+                    kind: HitZoneKind::Turret,
+                    offset,
+                    max_health: TURRET_HEALTH,
+                },
+                Health::new(TURRET_HEALTH),
+                Collider::new(TURRET_RADIUS),
+                CollisionDamage::new(BOSS_COLLISION_DAMAGE),
+                Mesh3d(boss_assets.mesh.clone()),
+                MeshMaterial3d(boss_assets.turret.clone()),
+                Transform::from_translation(translation + offset)
+                    .with_scale(Vec3::splat(TURRET_RADIUS)),
+            ));
+        }
+
+        // No Health on the core until the turrets are gone, so that
+        // missiles just bounce off its armor.
+        commands.spawn((
+            Name::new("boss_core"),
+            BossHitZone {
+                boss,
+                kind: HitZoneKind::Core,
+                offset: Vec3::ZERO,
+                max_health: CORE_HEALTH,
+            },
+            Collider::new(CORE_RADIUS),
+            CollisionDamage::new(BOSS_COLLISION_DAMAGE),
+            Mesh3d(boss_assets.mesh.clone()),
+            MeshMaterial3d(boss_assets.armored.clone()),
+            Transform::from_translation(translation).with_scale(Vec3::splat(CORE_RADIUS)),
+        ));
+
+        #[cfg(debug_assertions)]
+        info!("Boss wave! Level {}", event.level);
+    }
+}
+
+fn boss_movement(
+    mut query: Query<(&mut Boss, &Transform, &mut Velocity)>,
+    camera_query: Query<&Projection, With<Camera>>,
+    time: Res<Time>,
+) {
+    let Ok(projection) = camera_query.single() else { return; };
+    let (min, max) = play_area_bounds(projection);
+    let half_width = ((max.x - min.x) * 0.5 - BOSS_PATROL_MARGIN).max(0.0);
+
+    for (mut boss, xform, mut velocity) in query.iter_mut() {
+        boss.patrol_time += time.delta_secs();
+
+        let target = Vec3::new(
+            (boss.patrol_time * TAU / BOSS_PATROL_SECS).sin() * half_width,
+            0.0,
+            max.y - BOSS_PATROL_MARGIN,
+        );
+
+        let desired = (target - xform.translation).clamp_length_max(boss.phase.patrol_speed());
+        velocity.value = velocity.value.lerp(desired, (BOSS_STEERING * time.delta_secs()).min(1.0));
+    }
+}
+
+fn hit_zones_follow_boss(
+    bosses: Query<&Transform, With<Boss>>,
+    mut zones: Query<(&BossHitZone, &mut Transform), Without<Boss>>,
+) {
+    for (zone, mut xform) in zones.iter_mut() {
+        if let Ok(boss_xform) = bosses.get(zone.boss) {
+            xform.translation = boss_xform.translation + zone.offset;
+        }
+    }
+}
+
+fn update_boss_phase(
+    mut commands: Commands,
+    boss_assets: Res<BossAssets>,
+    mut bosses: Query<(Entity, &mut Boss)>,
+    zones: Query<(Entity, &BossHitZone, Option<&Health>)>,
+) {
+    for (boss_e, mut boss) in bosses.iter_mut() {
+        let mine = || zones.iter().filter(|(_, zone, _)| zone.boss == boss_e);
+        let turrets_left = mine()
+            .filter(|(_, zone, health)| {
+                zone.kind == HitZoneKind::Turret && health.is_some_and(|h| h.value > 0.0)
+            })
+            .count();
+        let core = mine().find(|(_, zone, _)| zone.kind == HitZoneKind::Core);
+
+        let next_phase = match (boss.phase, core) {
+            (BossPhase::Turrets, Some((core_e, zone, _))) if turrets_left == 0 => {
+                // Drop the armor.
+                commands.entity(core_e).insert((
+                    Health::new(zone.max_health),
+                    MeshMaterial3d(boss_assets.exposed.clone()),
+                ));
+                BossPhase::Core
+            }
+            (BossPhase::Core, Some((_, zone, Some(health))))
+                if health.value < zone.max_health * CORE_ENRAGED_FRACTION => BossPhase::Enraged,
+            (phase, _) => phase,
+        };
+
+        if next_phase != boss.phase {
+            boss.phase = next_phase;
+            boss.attack = Timer::from_seconds(next_phase.attack_secs(), TimerMode::Repeating);
+
+            #[cfg(debug_assertions)]
+            info!("Boss phase: {:?}", next_phase);
+        }
+    }
+}
+
+type ShipNotBoss = (With<Spaceship>, Without<Boss>);
+
+fn boss_attacks(
+    mut commands: Commands,
+    mut bosses: Query<(Entity, &mut Boss, &Transform)>,
+    zones: Query<(&BossHitZone, &Transform, Option<&Health>), Without<Boss>>,
+    spaceship: Query<(&Transform, &Velocity), ShipNotBoss>,
+    mut sound_event_writer: MessageWriter<SaucerShootingSoundEvent>,
+    missiles: MissileSpawner,
+    time: Res<Time>,
+) {
+    for (boss_e, mut boss, boss_xform) in bosses.iter_mut() {
+        if !boss.attack.tick(time.delta()).just_finished() {
+            continue;
+        }
+
+        // Everything this volley is aimed at whoever is closest.
+        let Some((ship_xform, ship_velocity)) = nearest_ship(boss_xform.translation, spaceship.iter()) else {
+            continue;
+        };
+
+        let aim_from = |origin: Vec3| aim_direction(
+            SaucerAimMode::Intercept,
+            origin,
+            ship_xform.translation,
+            ship_velocity.value,
+            missiles.config.saucer.missile_speed,
+            0.0,
+        );
+
+        match boss.phase {
+            // Each turret takes an aimed shot.
+            BossPhase::Turrets => {
+                for (zone, xform, health) in zones.iter() {
+                    if zone.boss != boss_e || zone.kind != HitZoneKind::Turret ||
+                       health.is_none_or(|h| h.value <= 0.0)
+                    {
+                        continue;
+                    }
+
+                    missiles.spawn(
+                        &mut commands,
+                        xform.translation,
+                        aim_from(xform.translation),
+                    );
+                }
+            }
+            // A fan of missiles from the core, centred on the ship.
+            BossPhase::Core => {
+                let direction = aim_from(boss_xform.translation);
+                let half = (CORE_FAN_MISSILES - 1) as f32 * 0.5;
+
+                for i in 0..CORE_FAN_MISSILES {
+                    let angle = (i as f32 - half) * CORE_FAN_ANGLE;
+                    missiles.spawn(
+                        &mut commands,
+                        boss_xform.translation,
+                        Quat::from_rotation_y(angle) * direction,
+                    );
+                }
+            }
+            // Missiles in every direction.
+            BossPhase::Enraged => {
+                let spin = boss.patrol_time;
+
+                for i in 0..ENRAGED_RING_MISSILES {
+                    let angle = spin + i as f32 * TAU / ENRAGED_RING_MISSILES as f32;
+                    missiles.spawn(
+                        &mut commands,
+                        boss_xform.translation,
+                        Quat::from_rotation_y(angle) * Vec3::Z,
+                    );
+                }
+            }
+        }
+
+        sound_event_writer.write(SaucerShootingSoundEvent);
+    }
+}
+
+// Once the core is gone, so is the boss.
+fn boss_defeated(
+    mut commands: Commands,
+    bosses: Query<(Entity, &Boss, &Transform, &Velocity, &Acceleration)>,
+    zones: Query<(&BossHitZone, Option<&Health>)>,
+    mut team_score: TeamScore,
+    mut animation_event_writer: MessageWriter<AsteroidCollisionAnimationEvent>,
+    mut sound_event_writer: MessageWriter<AsteroidCollisionSoundEvent>,
+) {
+    for (boss_e, boss, xform, velocity, acceleration) in bosses.iter() {
+        let core_alive = zones.iter().any(|(zone, health)| {
+            zone.boss == boss_e &&
+            zone.kind == HitZoneKind::Core &&
+            health.is_none_or(|h| h.value > 0.0)
+        });
+
+        if core_alive {
+            continue;
+        }
+
+        match boss.last_hit_by {
+            Some(player) => team_score.award(player, BOSS_POINTS),
+            None => team_score.share(BOSS_POINTS),
+        }
+
+        animation_event_writer.write(AsteroidCollisionAnimationEvent::new(xform, velocity, acceleration));
+        sound_event_writer.write(AsteroidCollisionSoundEvent);
+
+        commands.entity(boss_e).despawn();
+
+        #[cfg(debug_assertions)]
+        info!("Boss defeated! +{} points", BOSS_POINTS);
+    }
+}
+
+// Whoever hits the core last gets the credit for the boss, but by the time
+// boss_defeated notices, the core has gone, so the boss keeps track itself.
+fn remember_last_hit(
+    mut bosses: Query<&mut Boss>,
+    zones: Query<(&BossHitZone, &LastHitBy), Changed<LastHitBy>>,
+) {
+    for (zone, hit) in zones.iter() {
+        if let Ok(mut boss) = bosses.get_mut(zone.boss) {
+            boss.last_hit_by = Some(hit.0);
+        }
+    }
+}
+
+// The DespawnPlugin takes care of any hit zones with Health, which leaves
+// the boss itself and a still-armored core.
+type BossLeftovers = Or<(With<Boss>, (With<BossHitZone>, Without<Health>))>;
+
+fn despawn_boss(
+    mut commands: Commands,
+    query: Query<Entity, BossLeftovers>,
+) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn();
+    }
+}
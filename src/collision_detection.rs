@@ -1,16 +1,19 @@
-use bevy::{platform::collections::HashMap, prelude::*};
+use bevy::{ecs::system::SystemParam, platform::collections::HashMap, prelude::*};
 
 use crate::{
-    asteroids::{Asteroid, AsteroidCollisionAnimationEvent},
+    asteroids::{variants::Armor, Asteroid, AsteroidCollisionAnimationEvent},
+    boss::BossHitZone,
     health::Health,
     movement::{Acceleration, Velocity},
+    players::{LastHitBy, Player},
     saucer::{Saucer, SaucerMissile},
     schedule::InGameSet,
     sound::effects::AsteroidCollisionSoundEvent,
     spaceship::{
         Spaceship,
         SpaceshipMissile,
-        shield::{Shield, ShieldHitCooldown},
+        projectiles::Piercing,
+        shield::{Shield, ShieldHitCooldown, ShieldHitEvent},
     }
 };
 
@@ -70,6 +73,7 @@ impl Plugin for CollisionDetectionPlugin {
                     dispatch_collistion_events::<SpaceshipMissile>,
                     dispatch_collistion_events::<Saucer>,
                     dispatch_collistion_events::<SaucerMissile>,
+                    dispatch_collistion_events::<BossHitZone>,
                 ),
                 handle_collision_event,
             )
@@ -94,7 +98,7 @@ fn collision_detection(
                 if distance < collider_a.radius + collider_b.radius {
                     colliding_entities
                         .entry(entity_a)
-                        .or_insert_with(Vec::new)
+                        .or_default()
                         .push(entity_b);
                 }
             }
@@ -133,19 +137,79 @@ fn dispatch_collistion_events<T: Component>(
     }
 }
 
+// Everything that goes out when something gets hit.
+#[derive(SystemParam)]
+pub struct CollisionWriters<'w> {
+    sound_event_writer: MessageWriter<'w, AsteroidCollisionSoundEvent>,
+    animation_event_writer: MessageWriter<'w, AsteroidCollisionAnimationEvent>,
+    shield_hit_writer: MessageWriter<'w, ShieldHitEvent>,
+}
+
+// What does the damage, what takes it, and how it was moving at the time.
+#[derive(SystemParam)]
+pub struct DamageQueries<'w, 's> {
+    health_query: Query<'w, 's, &'static mut Health>,
+    armor_query: Query<'w, 's, &'static mut Armor>,
+    impact_query: Query<'w, 's, &'static mut Asteroid>,
+    asteroid_query: Query<'w, 's, (&'static Velocity, &'static Acceleration)>,
+    collision_damage_query: Query<'w, 's, (&'static CollisionDamage, &'static Name)>,
+}
+
+type ShipOrMissile = Or<(With<Spaceship>, With<SpaceshipMissile>)>;
+
+// The spaceship's side: the ships themselves, their shields, and whatever
+// they fire.
+#[derive(SystemParam)]
+pub struct ShipSideQueries<'w, 's> {
+    shield_hit_cd_query: Query<'w, 's, &'static mut ShieldHitCooldown>,
+    missile_query: Query<'w, 's, &'static Transform, ShipOrMissile>,
+    shield_query: Query<'w, 's, &'static Shield>,
+    spaceship_query: Query<'w, 's, (), With<Spaceship>>,
+    spaceship_missile_query: Query<'w, 's, (), With<SpaceshipMissile>>,
+    piercing_query: Query<'w, 's, &'static Piercing>,
+    player_query: Query<'w, 's, &'static Player>,
+}
+
 pub fn handle_collision_event(
+    mut commands: Commands,
     mut collision_event_reader: MessageReader<CollisionEvent>,
-    mut sound_event_writer: MessageWriter<AsteroidCollisionSoundEvent>,
-    mut animation_event_writer: MessageWriter<AsteroidCollisionAnimationEvent>,
-    mut health_query: Query<&mut Health>,
-    mut shield_hit_cd_query: Query<&mut ShieldHitCooldown>,
-    asteroid_query: Query<(&Velocity, &Acceleration)>,
-    missile_query: Query<&Transform, Or<(With<Spaceship>, With<SpaceshipMissile>)>>,
-    collision_damage_query: Query<(&CollisionDamage, &Name)>,
-    shield_query: Query<&Shield>,
-    spaceship_query: Query<(), With<Spaceship>>,
+    writers: CollisionWriters,
+    damage: DamageQueries,
+    ship_side: ShipSideQueries,
 ) {
+    let CollisionWriters {
+        mut sound_event_writer,
+        mut animation_event_writer,
+        mut shield_hit_writer,
+    } = writers;
+    let DamageQueries {
+        mut health_query,
+        mut armor_query,
+        mut impact_query,
+        asteroid_query,
+        collision_damage_query,
+    } = damage;
+    let ShipSideQueries {
+        mut shield_hit_cd_query,
+        missile_query,
+        shield_query,
+        spaceship_query,
+        spaceship_missile_query,
+        piercing_query,
+        player_query,
+    } = ship_side;
+
     for &CollisionEvent { entity, collided_entity } in collision_event_reader.read() {
+        // 0) The ship's own missiles (and mines, which get dropped right
+        //    behind it) don't hurt the ship or its shield, and vice versa.
+        let is_ship = |e: Entity| spaceship_query.get(e).is_ok() || shield_query.get(e).is_ok();
+        let is_ship_missile = |e: Entity| spaceship_missile_query.get(e).is_ok();
+        if (is_ship(entity) && is_ship_missile(collided_entity)) ||
+           (is_ship_missile(entity) && is_ship(collided_entity))
+        {
+            continue;
+        }
+
         // 1) If the ship has an active shield, ignore collisions on the ship itself.
         //    The shield will receive its own collision events.
         if spaceship_query.get(entity).is_ok() {
@@ -186,19 +250,36 @@ pub fn handle_collision_event(
             cd.timer.reset();
         }
 
-        // 4) Victim must have health
+        // 4) Piercing projectiles only damage a target once, however many
+        //    frames they spend going through it, and don't take any damage
+        //    themselves until they have run out of targets to pierce.
+        if let Ok(piercing) = piercing_query.get(collided_entity) {
+            if piercing.hit.contains(&entity) {
+                continue;
+            }
+        }
+        if let Ok(piercing) = piercing_query.get(entity) {
+            if piercing.remaining > 0 || piercing.hit.contains(&collided_entity) {
+                continue;
+            }
+        }
+
+        // 5) Victim must have health
         let Ok(mut health) = health_query.get_mut(entity) else {
             continue;
         };
 
-        // 5) Hitter must have collision damage
+        // 6) Hitter must have collision damage
         let Ok((collision_damage, _collided_name)) = collision_damage_query.get(collided_entity) else {
             continue;
         };
 
-        // 6) Apply damage
+        // 7) Apply damage
         let _before = health.value;
-        health.value -= collision_damage.amount;
+        match armor_query.get_mut(entity) {
+            Ok(mut armor) => armor.hit(&mut health, collision_damage.amount),
+            Err(_) => health.value -= collision_damage.amount,
+        }
 
         // Temporary debug log (remove or gate behind a debug feature once verified)
         #[cfg(debug_assertions)]
@@ -212,10 +293,33 @@ pub fn handle_collision_event(
             _collided_name
         );
 
-        // 7) Sound
+        // Remember who did it, so they get the points if this finishes it off.
+        // (It might already be on its way out, hence the try.)
+        if let Ok(player) = player_query.get(collided_entity) {
+            commands.entity(entity).try_insert(LastHitBy(*player));
+        }
+
+        // Remember which way the asteroid was hit, so that if this broke it
+        // up the fragments can fly off the same way.
+        if let (Ok(mut asteroid), Ok((velocity, _))) = (
+            impact_query.get_mut(entity),
+            asteroid_query.get(collided_entity),
+        ) {
+            asteroid.impact = velocity.value;
+        }
+
+        // 8) Sound.  The shield has its own, and its own impact effect.
+        if shield_query.get(entity).is_ok() {
+            shield_hit_writer.write(ShieldHitEvent {
+                shield: entity,
+                hitter: collided_entity,
+            });
+            continue;
+        }
+
         sound_event_writer.write(AsteroidCollisionSoundEvent);
 
-        // 8) Collision animation only for missile/ship collisions (per existing logic)
+        // 9) Collision animation only for missile/ship collisions (per existing logic)
         let Ok(xform) = missile_query.get(entity) else {
             continue;
         };
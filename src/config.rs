@@ -0,0 +1,634 @@
+use std::{fs, path::PathBuf, time::SystemTime};
+
+use bevy::{asset::io::file::FileAssetReader, prelude::*};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    asteroids::levels::{SplitPattern, BIG_ASTEROID, MEDIUM_ASTEROID, SMALL_ASTEROID},
+    difficulty::{Difficulty, DifficultySettings},
+    saucer::SaucerKind,
+};
+
+// Relative to the same folder Bevy loads the assets from, so it doesn't
+// matter where the game is started from.
+const DEFAULT_CONFIG_PATH: &str = "assets/config/game.ron";
+
+// How often we look to see if the file has been edited.
+const CONFIG_POLL_SECS: f32 = 1.0;
+
+// The smallest value allowed for anything that has to be above zero, like a
+// time or a rate.  Zero or less would panic in a Timer, or never come round
+// at all.
+const MIN_POSITIVE: f32 = 0.01;
+
+// Every fragment can split again, so this gets out of hand quickly.
+const MAX_FRAGMENTS: usize = 8;
+
+
+// All of the game's balance values in one place.  They are read from a RON
+// file at startup, can be overridden from the command line, and are picked
+// up again whenever the file is saved, so things can be tuned while playing.
+//
+// Anything missing from the file keeps its default, so the file only needs
+// to list what you want to change.
+#[derive(Resource, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GameConfig {
+    pub spaceship: SpaceshipConfig,
+    pub shield: ShieldConfig,
+    pub saucer: SaucerConfig,
+    pub asteroids: AsteroidsConfig,
+    pub despawn_distance: f32,  // anything this far from the middle is gone
+    pub custom_difficulty: DifficultySettings,
+    pub director: DirectorConfig,
+    pub players: PlayersConfig,
+    pub shop: ShopConfig,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SpaceshipConfig {
+    pub health: f32,
+    pub collision_damage: f32,
+    pub thrust: f32,
+    pub rotation_speed: f32,
+    pub roll_speed: f32,
+    // Upgrades are applied on top of these.
+    pub missile_speed: f32,
+    pub missile_rate: f32,  // shots per second
+    pub missile_max: usize,  // maximum number of missiles allowed in the air
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ShieldConfig {
+    pub hp: f32,
+    pub cooldown_secs: f32,  // to recharge an empty shield all the way
+    pub restitution: f32,  // how much of its speed an asteroid keeps when it bounces off
+    pub recoil: f32,  // how much of the hit the ship feels
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SaucerConfig {
+    pub collision_damage: f32,
+    pub missile_speed: f32,
+    pub missile_health: f32,
+    pub missile_range: f32,
+    pub large: SaucerAimConfig,
+    pub small: SaucerAimConfig,
+}
+
+// The inaccuracy cone (radians either side) at a score of zero, and the best
+// it can get as the score goes up.  The difficulty and the director scale
+// both.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SaucerAimConfig {
+    pub max_aim_error: f32,
+    pub min_aim_error: f32,
+}
+
+impl SaucerConfig {
+    pub fn aim(&self, kind: SaucerKind) -> &SaucerAimConfig {
+        match kind {
+            SaucerKind::Large => &self.large,
+            SaucerKind::Small => &self.small,
+        }
+    }
+}
+
+// One for each asteroid size.  Whatever size is missing keeps its built-in
+// values, but a size that is there needs all of them.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AsteroidsConfig {
+    pub small: AsteroidConfig,
+    pub medium: AsteroidConfig,
+    pub big: AsteroidConfig,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AsteroidConfig {
+    pub velocity_scalar: f32,
+    pub acceleration_scalar: f32,  // the difficulty multiplies this
+    pub max_rotate_speed: f32,
+    pub radius: f32,
+    pub health: f32,
+    pub collision_damage: f32,
+    pub mass: f32,
+    pub split: SplitPattern,  // how it breaks up when it is destroyed
+}
+
+impl AsteroidsConfig {
+    // By the asteroid's level, 0 being the small ones.
+    pub fn size(&self, level: usize) -> &AsteroidConfig {
+        match level {
+            0 => &self.small,
+            1 => &self.medium,
+            _ => &self.big,
+        }
+    }
+}
+
+// The adaptive difficulty director, and how far it is allowed to push
+// things either way.  1.0 is no change.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DirectorConfig {
+    pub enabled: bool,
+    pub waves_remembered: usize,  // how many recent waves it judges you on
+    pub asteroid_speed_min: f32,
+    pub asteroid_speed_max: f32,
+    pub saucer_spawn_rate_min: f32,
+    pub saucer_spawn_rate_max: f32,
+    pub saucer_aim_error_min: f32,  // smaller is a better shot
+    pub saucer_aim_error_max: f32,
+}
+
+// The upgrade shop between waves.  Without it, ore just piles up.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ShopConfig {
+    pub enabled: bool,
+}
+
+// Lives are per player.  A single player game keeps the original one ship
+// and you're out.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PlayersConfig {
+    pub solo_lives: u32,
+    pub coop_lives: u32,
+    pub respawn_secs: f32,  // before a player who lost a ship gets the next one
+}
+
+impl Default for GameConfig {
+    fn default() -> Self {
+        Self {
+            spaceship: SpaceshipConfig::default(),
+            shield: ShieldConfig::default(),
+            saucer: SaucerConfig::default(),
+            asteroids: AsteroidsConfig::default(),
+            despawn_distance: 100.0,
+            custom_difficulty: DifficultySettings::default(),
+            director: DirectorConfig::default(),
+            players: PlayersConfig::default(),
+            shop: ShopConfig::default(),
+        }
+    }
+}
+
+impl Default for AsteroidsConfig {
+    fn default() -> Self {
+        Self {
+            small: SMALL_ASTEROID,
+            medium: MEDIUM_ASTEROID,
+            big: BIG_ASTEROID,
+        }
+    }
+}
+
+impl Default for ShopConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+        }
+    }
+}
+
+impl Default for PlayersConfig {
+    fn default() -> Self {
+        Self {
+            solo_lives: 1,
+            coop_lives: 3,
+            respawn_secs: 3.0,
+        }
+    }
+}
+
+impl Default for DirectorConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            waves_remembered: 3,
+            asteroid_speed_min: 0.7,
+            asteroid_speed_max: 1.4,
+            saucer_spawn_rate_min: 0.6,
+            saucer_spawn_rate_max: 1.6,
+            saucer_aim_error_min: 0.5,
+            saucer_aim_error_max: 2.0,
+        }
+    }
+}
+
+impl Default for SpaceshipConfig {
+    fn default() -> Self {
+        Self {
+            health: 100.0,
+            collision_damage: 100.0,
+            thrust: 25.0,
+            rotation_speed: 2.5,
+            roll_speed: 2.5,
+            missile_speed: 50.0,
+            missile_rate: 4.0,
+            missile_max: 3,
+        }
+    }
+}
+
+impl Default for ShieldConfig {
+    fn default() -> Self {
+        Self {
+            hp: 60.0,
+            cooldown_secs: 30.0,
+            restitution: 0.8,
+            recoil: 0.15,
+        }
+    }
+}
+
+impl Default for SaucerConfig {
+    fn default() -> Self {
+        Self {
+            collision_damage: 100.0,
+            missile_speed: 40.0,
+            missile_health: 1.0,
+            missile_range: 80.0,
+            // The large saucer fires at random anyway.
+            large: SaucerAimConfig {
+                max_aim_error: 0.0,
+                min_aim_error: 0.0,
+            },
+            small: SaucerAimConfig {
+                max_aim_error: 0.35,
+                min_aim_error: 0.04,
+            },
+        }
+    }
+}
+
+impl Default for SaucerAimConfig {
+    fn default() -> Self {
+        Self {
+            max_aim_error: 0.0,
+            min_aim_error: 0.0,
+        }
+    }
+}
+
+impl GameConfig {
+    fn load(source: &ConfigSource) -> Result<Self, String> {
+        let config = match fs::read_to_string(&source.path) {
+            Ok(text) => ron::from_str(&text).map_err(|e| format!("{}: {}", source.path.display(), e))?,
+            // No file is fine, everything just keeps its default.  It's
+            // probably not what was meant though, so say so.
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                warn!("{} not found, using the default config", source.path.display());
+                GameConfig::default()
+            }
+            Err(e) => return Err(format!("{}: {}", source.path.display(), e)),
+        };
+
+        config.with_overrides(&source.overrides)?.validated()
+    }
+
+    // The file is reloaded while the game is running, so one typo mustn't be
+    // able to crash it.  Anything that ends up in a Timer, a Duration, a
+    // random range or a division gets checked here, and a bad value fails the
+    // whole load.  That way a reload keeps the last good config, and startup
+    // uses the defaults.
+    fn validated(self) -> Result<Self, String> {
+        // It has to be a real number, and no smaller than the minimum.
+        let check = |key: &str, value: f32, min: f32| {
+            if value.is_finite() && value >= min {
+                Ok(())
+            }
+            else {
+                Err(format!("{} is {}, it needs to be at least {}", key, value, min))
+            }
+        };
+
+        check("spaceship.missile_speed", self.spaceship.missile_speed, MIN_POSITIVE)?;
+        check("spaceship.missile_rate", self.spaceship.missile_rate, MIN_POSITIVE)?;
+        check("shield.hp", self.shield.hp, MIN_POSITIVE)?;
+        check("shield.cooldown_secs", self.shield.cooldown_secs, MIN_POSITIVE)?;
+        check("saucer.missile_speed", self.saucer.missile_speed, MIN_POSITIVE)?;
+        check("despawn_distance", self.despawn_distance, MIN_POSITIVE)?;
+        check("players.respawn_secs", self.players.respawn_secs, 0.0)?;
+        check("custom_difficulty.asteroid_spawn_secs", self.custom_difficulty.asteroid_spawn_secs, MIN_POSITIVE)?;
+        check("custom_difficulty.saucer_spawn_secs", self.custom_difficulty.saucer_spawn_secs, MIN_POSITIVE)?;
+        check("custom_difficulty.shield_decay", self.custom_difficulty.shield_decay, 0.0)?;
+        check("custom_difficulty.saucer_aim_scalar", self.custom_difficulty.saucer_aim_scalar, 0.0)?;
+        check("custom_difficulty.saucer_missile_damage", self.custom_difficulty.saucer_missile_damage, 0.0)?;
+        check("custom_difficulty.asteroid_acceleration_scalar", self.custom_difficulty.asteroid_acceleration_scalar, 0.0)?;
+
+        for (name, aim) in [("large", &self.saucer.large), ("small", &self.saucer.small)] {
+            check(&format!("saucer.{}.max_aim_error", name), aim.max_aim_error, 0.0)?;
+            check(&format!("saucer.{}.min_aim_error", name), aim.min_aim_error, 0.0)?;
+            if aim.min_aim_error > aim.max_aim_error {
+                return Err(format!(
+                    "saucer.{}.min_aim_error is {}, it can't be more than max_aim_error ({})",
+                    name, aim.min_aim_error, aim.max_aim_error,
+                ));
+            }
+        }
+
+        // The director scales things by anything from its min to its max,
+        // with 1.0 (no change) in between.
+        let director = &self.director;
+        if director.waves_remembered == 0 {
+            return Err(String::from("director.waves_remembered is 0, it needs to be at least 1"));
+        }
+        for (name, min, max) in [
+            ("asteroid_speed", director.asteroid_speed_min, director.asteroid_speed_max),
+            ("saucer_spawn_rate", director.saucer_spawn_rate_min, director.saucer_spawn_rate_max),
+            ("saucer_aim_error", director.saucer_aim_error_min, director.saucer_aim_error_max),
+        ] {
+            let in_bounds = min.is_finite() && max.is_finite() &&
+                (0.0..=1.0).contains(&min) && max >= 1.0;
+            if !in_bounds {
+                return Err(format!(
+                    "director.{0}_min is {1} and director.{0}_max is {2}, they need to be 0 <= min <= 1 <= max",
+                    name, min, max,
+                ));
+            }
+        }
+
+        let asteroids = &self.asteroids;
+        for (name, size) in [("small", &asteroids.small), ("medium", &asteroids.medium), ("big", &asteroids.big)] {
+            let key = |field: &str| format!("asteroids.{}.{}", name, field);
+            check(&key("velocity_scalar"), size.velocity_scalar, 0.0)?;
+            check(&key("acceleration_scalar"), size.acceleration_scalar, 0.0)?;
+            check(&key("max_rotate_speed"), size.max_rotate_speed, MIN_POSITIVE)?;
+            check(&key("radius"), size.radius, MIN_POSITIVE)?;
+            check(&key("mass"), size.mass, MIN_POSITIVE)?;
+            check(&key("split.spread_angle"), size.split.spread_angle, 0.0)?;
+            check(&key("split.speed_inheritance"), size.split.speed_inheritance, 0.0)?;
+            check(&key("split.split_speed"), size.split.split_speed, 0.0)?;
+            check(&key("split.impact_influence"), size.split.impact_influence, 0.0)?;
+            if size.split.fragments > MAX_FRAGMENTS {
+                return Err(format!(
+                    "{} is {}, it can't be more than {}",
+                    key("split.fragments"), size.split.fragments, MAX_FRAGMENTS,
+                ));
+            }
+        }
+
+        Ok(self)
+    }
+
+    // Each override is a dotted path to a setting and a RON value, e.g.
+    // "shield.hp=80".  We go through ron::Value so we don't need to know
+    // about every field here.
+    fn with_overrides(self, overrides: &[(String, String)]) -> Result<Self, String> {
+        if overrides.is_empty() {
+            return Ok(self);
+        }
+
+        let text = ron::to_string(&self).map_err(|e| e.to_string())?;
+        let mut root: ron::Value = ron::from_str(&text).map_err(|e| e.to_string())?;
+
+        for (key, raw) in overrides {
+            let value: ron::Value = ron::from_str(raw).map_err(|e| format!("{}: {}", key, e))?;
+
+            let mut slot = &mut root;
+            for part in key.split('.') {
+                let ron::Value::Map(map) = slot else {
+                    return Err(format!("{}: {} isn't a section", key, part));
+                };
+                slot = map
+                    .get_mut(&ron::Value::String(part.to_string()))
+                    .ok_or_else(|| format!("unknown setting {}", key))?;
+            }
+            *slot = value;
+        }
+
+        root.into_rust().map_err(|e| e.to_string())
+    }
+}
+
+// Where the config came from, so we can load it again when it changes.
+#[derive(Resource, Debug)]
+struct ConfigSource {
+    path: PathBuf,
+    overrides: Vec<(String, String)>,  // from the command line, these always win
+    modified: Option<SystemTime>,
+    poll: Timer,
+}
+
+impl ConfigSource {
+    //   --config <path>        use a different config file
+    //   --set <key>=<value>    override a single setting, e.g. --set shield.hp=80
+    fn from_args() -> Self {
+        let mut path = FileAssetReader::get_base_path().join(DEFAULT_CONFIG_PATH);
+        let mut overrides = vec![];
+
+        let mut args = std::env::args().skip(1);
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--config" => {
+                    if let Some(value) = args.next() {
+                        path = PathBuf::from(value);
+                    }
+                }
+                "--set" => {
+                    match args.next().as_deref().and_then(|s| s.split_once('=')) {
+                        Some((key, value)) => overrides.push((key.trim().to_string(), value.trim().to_string())),
+                        None => warn!("--set needs a <key>=<value>"),
+                    }
+                }
+                _ => (),
+            }
+        }
+
+        Self {
+            path,
+            overrides,
+            modified: None,
+            poll: Timer::from_seconds(CONFIG_POLL_SECS, TimerMode::Repeating),
+        }
+    }
+
+    fn file_modified(&self) -> Option<SystemTime> {
+        fs::metadata(&self.path).and_then(|m| m.modified()).ok()
+    }
+}
+
+
+pub struct ConfigPlugin;
+
+impl Plugin for ConfigPlugin {
+    fn build(&self, app: &mut App) {
+        // Load it straight away, so it is there before anything else starts
+        // up.
+        let mut source = ConfigSource::from_args();
+        source.modified = source.file_modified();
+
+        let config = GameConfig::load(&source).unwrap_or_else(|e| {
+            error!("Couldn't load the config, using the defaults: {}", e);
+            GameConfig::default()
+        });
+
+        app.insert_resource(config)
+        .insert_resource(source)
+        .add_systems(Update, (
+            reload_changed_config,
+            apply_custom_difficulty.run_if(resource_changed::<GameConfig>),
+        ).chain());
+    }
+}
+
+fn reload_changed_config(
+    mut source: ResMut<ConfigSource>,
+    mut config: ResMut<GameConfig>,
+    time: Res<Time>,
+) {
+    if !source.poll.tick(time.delta()).just_finished() {
+        return;
+    }
+
+    let modified = source.file_modified();
+    if modified == source.modified {
+        return;
+    }
+    source.modified = modified;
+
+    // A half-saved or broken file shouldn't wreck the game in progress, so
+    // we keep what we had until it is fixed.
+    match GameConfig::load(&source) {
+        Ok(new_config) => {
+            if new_config != *config {
+                info!("Reloaded {}", source.path.display());
+                *config = new_config;
+            }
+        }
+        Err(e) => warn!("Couldn't reload the config: {}", e),
+    }
+}
+
+fn apply_custom_difficulty(
+    config: Res<GameConfig>,
+    mut difficulty: ResMut<Difficulty>,
+) {
+    if difficulty.custom != config.custom_difficulty {
+        difficulty.custom = config.custom_difficulty;
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn set(key: &str, value: &str) -> (String, String) {
+        (key.to_string(), value.to_string())
+    }
+
+    #[test]
+    fn the_defaults_are_valid() {
+        assert_eq!(GameConfig::default().validated(), Ok(GameConfig::default()));
+    }
+
+    #[test]
+    fn the_shipped_config_file_loads() {
+        let text = fs::read_to_string(DEFAULT_CONFIG_PATH).unwrap();
+        let config: GameConfig = ron::from_str(&text).unwrap();
+        assert!(config.validated().is_ok());
+    }
+
+    #[test]
+    fn overrides_replace_nested_values() {
+        let config = GameConfig::default()
+            .with_overrides(&[
+                set("shield.hp", "80.0"),
+                set("asteroids.big.split.fragments", "3"),
+                set("director.enabled", "true"),
+            ])
+            .unwrap();
+
+        assert_eq!(config.shield.hp, 80.0);
+        assert_eq!(config.asteroids.big.split.fragments, 3);
+        assert!(config.director.enabled);
+        // Everything else is left alone.
+        assert_eq!(config.spaceship, GameConfig::default().spaceship);
+    }
+
+    #[test]
+    fn unknown_settings_are_an_error() {
+        assert!(GameConfig::default().with_overrides(&[set("shield.colour", "1.0")]).is_err());
+        assert!(GameConfig::default().with_overrides(&[set("nonsense", "1.0")]).is_err());
+        // A value isn't a section, so there's nothing below it.
+        assert!(GameConfig::default().with_overrides(&[set("shield.hp.max", "1.0")]).is_err());
+    }
+
+    #[test]
+    fn bad_override_values_are_an_error() {
+        assert!(GameConfig::default().with_overrides(&[set("shield.hp", "lots")]).is_err());
+        assert!(GameConfig::default().with_overrides(&[set("shield.hp", "(")]).is_err());
+    }
+
+    #[test]
+    fn values_that_would_panic_are_rejected() {
+        let bad = [
+            set("spaceship.missile_rate", "0.0"),
+            set("shield.cooldown_secs", "-1.0"),
+            set("shield.hp", "0.0"),
+            set("saucer.missile_speed", "0.0"),
+            set("despawn_distance", "-5.0"),
+            set("players.respawn_secs", "-1.0"),
+            set("custom_difficulty.saucer_spawn_secs", "0.0"),
+            set("custom_difficulty.saucer_aim_scalar", "-0.5"),
+            set("saucer.small.max_aim_error", "-0.1"),
+            set("asteroids.medium.max_rotate_speed", "0.0"),
+            set("asteroids.small.velocity_scalar", "-1.0"),
+            set("asteroids.big.split.spread_angle", "-1.0"),
+            set("asteroids.big.split.impact_influence", "-0.5"),
+            set("saucer.small.min_aim_error", "0.5"),
+            set("custom_difficulty.saucer_missile_damage", "-1.0"),
+            set("custom_difficulty.asteroid_acceleration_scalar", "-1.0"),
+            set("director.waves_remembered", "0"),
+            set("director.asteroid_speed_min", "-1.0"),
+            set("director.asteroid_speed_min", "1.5"),
+            set("director.asteroid_speed_max", "0.5"),
+            set("director.saucer_spawn_rate_max", "inf"),
+            set("director.saucer_aim_error_min", "1.1"),
+        ];
+
+        for (key, value) in bad {
+            let result = GameConfig::default()
+                .with_overrides(&[(key.clone(), value)])
+                .and_then(GameConfig::validated);
+            assert!(result.is_err(), "{} should have been rejected", key);
+        }
+    }
+
+    #[test]
+    fn too_many_fragments_are_rejected() {
+        let mut config = GameConfig::default();
+        config.asteroids.big.split.fragments = MAX_FRAGMENTS;
+        assert!(config.clone().validated().is_ok());
+
+        config.asteroids.big.split.fragments = MAX_FRAGMENTS + 1;
+        assert!(config.validated().is_err());
+    }
+
+    #[test]
+    fn director_bounds_can_sit_on_no_change() {
+        let config = GameConfig::default()
+            .with_overrides(&[
+                set("director.asteroid_speed_min", "1.0"),
+                set("director.asteroid_speed_max", "1.0"),
+                set("director.saucer_aim_error_min", "0.0"),
+            ])
+            .unwrap();
+        assert!(config.validated().is_ok());
+    }
+
+    #[test]
+    fn not_a_number_is_rejected() {
+        let mut config = GameConfig::default();
+        config.spaceship.missile_speed = f32::NAN;
+        assert!(config.validated().is_err());
+    }
+}
@@ -1,18 +1,29 @@
-#![cfg(feature = "debug")] # Only compile this file if the "debug" feature is enabled.
+#![cfg(feature = "debug")] // Only compile this file if the "debug" feature is enabled.
 use bevy::prelude::*;
 
 use crate::{
+    asset_loader::SceneAssets,
+    config::GameConfig,
+    director::Director,
     movement::Velocity,
+    physics::PhysicsSettings,
     schedule::InGameSet,
+    state::GameSetup,
+    waves::WaveStats,
 };
 
+#[derive(Component, Debug)]
+pub struct DebugOverlay;
+
 pub struct DebugPlugin;
 
 impl Plugin for DebugPlugin {
     fn build(&self, app: &mut App) {
         app.add_systems(
             Update,
-            print_position.after(InGameSet::EntityUpdates));
+            print_position.after(InGameSet::EntityUpdates))
+        .add_systems(GameSetup, spawn_debug_overlay)
+        .add_systems(Update, update_debug_overlay);
     }
 }
 
@@ -21,3 +32,62 @@ fn print_position(query: Query<(Entity, &Transform), With<Velocity>>) {
         println!("Entity: {:?}, Position: {:?}", entity, position.translation);
     }
 }
+
+fn spawn_debug_overlay(
+    mut commands: Commands,
+    scene_assets: Res<SceneAssets>,
+) {
+    commands.spawn((
+        Name::new("debug_overlay"),
+        Node {
+            position_type: PositionType::Absolute,
+            bottom: Val::Percent(2.0),
+            right: Val::Percent(2.0),
+            ..default()
+        },
+        Text::new(""),
+        TextFont {
+            font: scene_assets.font("font"),
+            font_size: 14.0,
+            ..default()
+        },
+        TextColor(Color::srgb(0.6, 1.0, 0.6)),
+        DebugOverlay,
+    ));
+}
+
+// What the adaptive difficulty director is up to, what it will make of the
+// current wave, and whether momentum is on.
+fn update_debug_overlay(
+    director: Res<Director>,
+    stats: Res<WaveStats>,
+    config: Res<GameConfig>,
+    physics: Res<PhysicsSettings>,
+    mut overlay: Query<&mut Text, With<DebugOverlay>>,
+) {
+    let Ok(mut text) = overlay.single_mut() else {
+        return;
+    };
+
+    let mut lines = vec![
+        format!("Momentum: {}", if physics.momentum { "on" } else { "off" }),
+        format!("Director: {}", if config.director.enabled { "on" } else { "off" }),
+        format!("  skill {:+.2}", director.skill),
+        format!("  asteroid speed x{:.2}", director.asteroid_speed),
+        format!("  saucer spawns x{:.2}", director.saucer_spawn_rate),
+        format!("  saucer aim error x{:.2}", director.saucer_aim_error),
+    ];
+
+    for rating in director.recent.iter() {
+        lines.push(format!("  wave: {:+.2}", rating));
+    }
+
+    lines.push(format!(
+        "  this wave: {:.0}% acc, {:.0}s, {:.0} dmg",
+        stats.accuracy() * 100.0,
+        stats.secs,
+        stats.damage_taken,
+    ));
+
+    text.0 = lines.join("\n");
+}
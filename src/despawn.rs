@@ -1,13 +1,41 @@
+#[cfg(debug_assertions)]
+use std::collections::BTreeMap;
+
 use bevy::prelude::*;
 
 use crate::{
-    asteroids::{Asteroid, AsteroidSpawnChildrenEvent}, health::Health, movement::{Acceleration, Rotation, Velocity}, schedule::InGameSet, sound::effects::GameSoundEffects, spaceship::{
+    asteroids::{Asteroid, AsteroidSpawnChildrenEvent}, config::GameConfig, health::Health, movement::{Acceleration, Rotation, Velocity}, saucer::Saucer, schedule::InGameSet, sound::effects::GameSoundEffects, spaceship::{
         Spaceship,
         shield::Shield,
     }, state::GameState
 };
 
-const DESPAWN_DISTANCE: f32 = 100.0;
+// Every so often (in debug builds) we count up the entities by name, and
+// complain about any name whose count has gone up for several checks in a
+// row.  That is usually something that is never being cleaned up.
+#[cfg(debug_assertions)]
+const LEAK_CHECK_INTERVAL_SECS: f32 = 10.0;
+#[cfg(debug_assertions)]
+const LEAK_CHECK_GROWTH_LIMIT: u32 = 3;
+
+#[cfg(debug_assertions)]
+#[derive(Resource, Debug)]
+struct LeakCheck {
+    timer: Timer,
+    last_counts: BTreeMap<String, usize>,
+    growth: BTreeMap<String, u32>,
+}
+
+#[cfg(debug_assertions)]
+impl Default for LeakCheck {
+    fn default() -> Self {
+        Self {
+            timer: Timer::from_seconds(LEAK_CHECK_INTERVAL_SECS, TimerMode::Repeating),
+            last_counts: default(),
+            growth: default(),
+        }
+    }
+}
 
 pub struct DespawnPlugin;
 
@@ -18,6 +46,7 @@ impl Plugin for DespawnPlugin {
             (
                 despawn_far_away_entities::<Asteroid>,
                 despawn_far_away_entities::<Spaceship>,
+                despawn_far_away_entities::<Saucer>,
                 despawn_dead_entities,
                 despawn_old_audiosink_entities,
             ).in_set(InGameSet::DespawnEntities),
@@ -26,17 +55,25 @@ impl Plugin for DespawnPlugin {
             OnEnter(GameState::GameOver),
             despawn_all_entities::<Health>,
         );
+
+        #[cfg(debug_assertions)]
+        app.init_resource::<LeakCheck>()
+        .add_systems(
+            Update,
+            report_entity_counts.run_if(in_state(GameState::InGame)),
+        );
     }
 }
 
 fn despawn_far_away_entities<T: Component>(
     mut commands: Commands,
-    query: Query<(Entity, &Transform), With<T>>
+    query: Query<(Entity, &Transform), With<T>>,
+    config: Res<GameConfig>,
 ) {
     for (entity, transform) in query.iter() {
         let distance = transform.translation.distance(Vec3::ZERO);
 
-        if distance > DESPAWN_DISTANCE {
+        if distance > config.despawn_distance {
             commands.entity(entity).despawn();
         }
     }
@@ -69,7 +106,9 @@ fn despawn_dead_entities(
                             velocity,
                             rotation,
                             acceleration,
-                            asteroid.level
+                            asteroid.level,
+                            asteroid.impact,
+                            asteroid.variant,
                         )
                     );
                 }
@@ -109,3 +148,45 @@ fn despawn_old_audiosink_entities(
         }
     }
 }
+
+#[cfg(debug_assertions)]
+fn report_entity_counts(
+    mut leak_check: ResMut<LeakCheck>,
+    query: Query<&Name>,
+    time: Res<Time>,
+) {
+    if !leak_check.timer.tick(time.delta()).just_finished() {
+        return;
+    }
+
+    let mut counts: BTreeMap<String, usize> = default();
+    for name in query.iter() {
+        *counts.entry(name.as_str().to_string()).or_default() += 1;
+    }
+
+    let report = counts
+        .iter()
+        .map(|(name, count)| format!("{}: {}", name, count))
+        .collect::<Vec<_>>()
+        .join(", ");
+    info!("Entity counts: {}", report);
+
+    for (name, count) in counts.iter() {
+        let grew = leak_check.last_counts.get(name).is_some_and(|last| count > last);
+        let growth = leak_check.growth.entry(name.clone()).or_default();
+
+        *growth = if grew { *growth + 1 } else { 0 };
+
+        if *growth >= LEAK_CHECK_GROWTH_LIMIT {
+            warn!(
+                "Possible entity leak: {} '{}' entities, up for {} checks in a row",
+                count,
+                name,
+                growth,
+            );
+        }
+    }
+
+    leak_check.growth.retain(|name, _| counts.contains_key(name));
+    leak_check.last_counts = counts;
+}
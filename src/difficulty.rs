@@ -0,0 +1,169 @@
+use std::time::Duration;
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    asteroids::AsteroidSpawnTimer,
+    director::Director,
+    saucer::SaucerSpawnTimer,
+};
+
+// The Custom settings come from the config file, so they could be anything.
+// Nothing spawns faster than this, however small the number.
+const MIN_SPAWN_SECS: f32 = 0.1;
+
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DifficultyLevel {
+    Easy,
+    #[default]
+    Normal,
+    Hard,
+    Insane,
+    Custom,
+}
+
+impl DifficultyLevel {
+    pub const ALL: [DifficultyLevel; 5] = [
+        DifficultyLevel::Easy,
+        DifficultyLevel::Normal,
+        DifficultyLevel::Hard,
+        DifficultyLevel::Insane,
+        DifficultyLevel::Custom,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            DifficultyLevel::Easy => "Easy",
+            DifficultyLevel::Normal => "Normal",
+            DifficultyLevel::Hard => "Hard",
+            DifficultyLevel::Insane => "Insane",
+            DifficultyLevel::Custom => "Custom",
+        }
+    }
+
+    // Wraps around, so the menu can just keep cycling.
+    pub fn next(&self) -> Self {
+        let index = Self::ALL.iter().position(|d| d == self).unwrap_or(0);
+        Self::ALL[(index + 1) % Self::ALL.len()]
+    }
+
+    pub fn prev(&self) -> Self {
+        let index = Self::ALL.iter().position(|d| d == self).unwrap_or(0);
+        Self::ALL[(index + Self::ALL.len() - 1) % Self::ALL.len()]
+    }
+}
+
+// The balance values that change with the difficulty.  Everything else
+// stays the same whatever you pick.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DifficultySettings {
+    pub asteroid_spawn_secs: f32,  // how long between checks for a cleared wave
+    pub saucer_spawn_secs: f32,
+    pub saucer_missile_damage: f32,
+    pub asteroid_acceleration_scalar: f32,  // multiplies each size's own value
+    pub shield_decay: f32,  // HP per second.
+    pub saucer_aim_scalar: f32,  // multiplies how far saucer shots can miss by
+}
+
+const EASY: DifficultySettings = DifficultySettings {
+    asteroid_spawn_secs: 5.0,
+    saucer_spawn_secs: 60.0,
+    saucer_missile_damage: 4.0,
+    asteroid_acceleration_scalar: 0.6,
+    shield_decay: 2.5,
+    saucer_aim_scalar: 1.5,
+};
+
+const NORMAL: DifficultySettings = DifficultySettings {
+    asteroid_spawn_secs: 4.0,
+    saucer_spawn_secs: 45.0,
+    saucer_missile_damage: 7.0,
+    asteroid_acceleration_scalar: 1.0,
+    shield_decay: 4.0,
+    saucer_aim_scalar: 1.0,
+};
+
+const HARD: DifficultySettings = DifficultySettings {
+    asteroid_spawn_secs: 3.0,
+    saucer_spawn_secs: 35.0,
+    saucer_missile_damage: 10.0,
+    asteroid_acceleration_scalar: 1.4,
+    shield_decay: 5.5,
+    saucer_aim_scalar: 0.75,
+};
+
+const INSANE: DifficultySettings = DifficultySettings {
+    asteroid_spawn_secs: 2.0,
+    saucer_spawn_secs: 25.0,
+    saucer_missile_damage: 14.0,
+    asteroid_acceleration_scalar: 2.0,
+    shield_decay: 8.0,
+    saucer_aim_scalar: 0.5,
+};
+
+impl Default for DifficultySettings {
+    fn default() -> Self {
+        NORMAL
+    }
+}
+
+
+#[derive(Resource, Debug, Default)]
+pub struct Difficulty {
+    pub level: DifficultyLevel,
+    pub custom: DifficultySettings,  // used when the level is Custom, set from the GameConfig
+}
+
+impl Difficulty {
+    pub fn settings(&self) -> DifficultySettings {
+        match self.level {
+            DifficultyLevel::Easy => EASY,
+            DifficultyLevel::Normal => NORMAL,
+            DifficultyLevel::Hard => HARD,
+            DifficultyLevel::Insane => INSANE,
+            DifficultyLevel::Custom => self.custom,
+        }
+    }
+}
+
+
+pub struct DifficultyPlugin;
+
+impl Plugin for DifficultyPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Difficulty>()
+        .add_systems(Update,
+            apply_difficulty_timers.run_if(
+                resource_changed::<Difficulty>.or(resource_changed::<Director>)
+            ),
+        );
+    }
+}
+
+// The spawn timers live with their own plugins; we just change how long
+// they run for.  The director can bring the saucers round more (or less)
+// often on top of that.
+fn apply_difficulty_timers(
+    difficulty: Res<Difficulty>,
+    director: Res<Director>,
+    mut asteroid_timer: ResMut<AsteroidSpawnTimer>,
+    mut saucer_timer: ResMut<SaucerSpawnTimer>,
+) {
+    let settings = difficulty.settings();
+    let saucer_secs = settings.saucer_spawn_secs / director.saucer_spawn_rate.max(0.1);
+
+    asteroid_timer.timer.set_duration(spawn_duration(settings.asteroid_spawn_secs));
+    saucer_timer.timer.set_duration(spawn_duration(saucer_secs));
+
+    #[cfg(debug_assertions)]
+    info!("Difficulty: {:?} {:?}", difficulty.level, settings);
+}
+
+// Too small (or not a number) gets the minimum, and too big to be a Duration
+// means it never comes round at all.
+fn spawn_duration(secs: f32) -> Duration {
+    Duration::try_from_secs_f32(secs.max(MIN_SPAWN_SECS)).unwrap_or(Duration::MAX)
+}
@@ -0,0 +1,197 @@
+use std::collections::VecDeque;
+
+use bevy::prelude::*;
+
+use crate::{
+    config::{DirectorConfig, GameConfig},
+    modes::GameEndReason,
+    state::GameState,
+    waves::{WaveClearedEvent, WaveStats},
+};
+
+// What counts as a good wave.  Doing better than these pushes the
+// difficulty up, doing worse eases it off.
+const PAR_ACCURACY: f32 = 0.5;
+const PAR_CLEAR_SECS: f32 = 45.0;
+const PAR_DAMAGE: f32 = 50.0;  // taking this much is as bad as it gets
+
+// Getting killed says more than any of the numbers do.
+const DIED_RATING: f32 = -1.0;
+
+
+// How the player did in one wave, from -1.0 (struggling) to 1.0 (bored).
+fn rate_wave(stats: &WaveStats) -> f32 {
+    let accuracy_rating = ((stats.accuracy() - PAR_ACCURACY) / PAR_ACCURACY).clamp(-1.0, 1.0);
+    let time_rating = ((PAR_CLEAR_SECS - stats.secs) / PAR_CLEAR_SECS).clamp(-1.0, 1.0);
+    let damage_rating = (1.0 - 2.0 * stats.damage_taken / PAR_DAMAGE).clamp(-1.0, 1.0);
+
+    (accuracy_rating + time_rating + damage_rating) / 3.0
+}
+
+// Watches how the player is getting on, and eases off or pushes harder to
+// suit.  It only ever works within the bounds in the DirectorConfig, and
+// everything is left alone (1.0) while it is switched off.
+//
+// The history carries over from one game to the next, so dying early on
+// makes the next game a bit kinder.
+#[derive(Resource, Debug)]
+pub struct Director {
+    pub recent: VecDeque<f32>,  // ratings of the last few waves
+    pub skill: f32,  // average rating of the recent waves
+    pub asteroid_speed: f32,  // scales asteroid velocity and acceleration
+    pub saucer_spawn_rate: f32,  // scales how often saucers turn up
+    pub saucer_aim_error: f32,  // scales how far saucer shots can miss by
+}
+
+impl Default for Director {
+    fn default() -> Self {
+        Self {
+            recent: VecDeque::new(),
+            skill: 0.0,
+            asteroid_speed: 1.0,
+            saucer_spawn_rate: 1.0,
+            saucer_aim_error: 1.0,
+        }
+    }
+}
+
+impl Director {
+    fn record(&mut self, rating: f32, config: &DirectorConfig) {
+        self.recent.push_back(rating);
+        while self.recent.len() > config.waves_remembered.max(1) {
+            self.recent.pop_front();
+        }
+
+        self.skill = self.recent.iter().sum::<f32>() / self.recent.len() as f32;
+
+        self.asteroid_speed = scale(self.skill, config.asteroid_speed_min, config.asteroid_speed_max);
+        self.saucer_spawn_rate = scale(self.skill, config.saucer_spawn_rate_min, config.saucer_spawn_rate_max);
+        // Better players get better shots, so this one goes the other way.
+        self.saucer_aim_error = scale(-self.skill, config.saucer_aim_error_min, config.saucer_aim_error_max);
+
+        #[cfg(debug_assertions)]
+        info!("Director: {:+.2} -> {:?}", rating, self);
+    }
+}
+
+// 0.0 is no change, 1.0 goes all the way to max, -1.0 all the way to min.
+fn scale(t: f32, min: f32, max: f32) -> f32 {
+    if t >= 0.0 {
+        1.0 + (max - 1.0) * t
+    }
+    else {
+        1.0 + (1.0 - min) * t
+    }
+}
+
+
+pub struct DirectorPlugin;
+
+impl Plugin for DirectorPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Director>()
+        .add_systems(Update, (
+            switch_director,
+            rate_cleared_waves.run_if(director_enabled),
+        ))
+        .add_systems(OnEnter(GameState::GameOver),
+            rate_fatal_wave.run_if(director_enabled.and(ended_by_death)),
+        );
+    }
+}
+
+fn director_enabled(config: Res<GameConfig>) -> bool {
+    config.director.enabled
+}
+
+// Running out of time, or stopping a game nothing could hurt you in, says
+// nothing about how hard the game was.
+fn ended_by_death(end_reason: Res<GameEndReason>) -> bool {
+    *end_reason == GameEndReason::PlayersOut
+}
+
+// Turning it off puts everything back the way it was.
+fn switch_director(
+    config: Res<GameConfig>,
+    mut director: ResMut<Director>,
+) {
+    if config.is_changed() && !config.director.enabled && !director.recent.is_empty() {
+        *director = Director::default();
+    }
+}
+
+fn rate_cleared_waves(
+    mut wave_cleared_reader: MessageReader<WaveClearedEvent>,
+    stats: Res<WaveStats>,
+    config: Res<GameConfig>,
+    mut director: ResMut<Director>,
+) {
+    for _ in wave_cleared_reader.read() {
+        director.record(rate_wave(&stats), &config.director);
+    }
+}
+
+fn rate_fatal_wave(
+    config: Res<GameConfig>,
+    mut director: ResMut<Director>,
+) {
+    director.record(DIED_RATING, &config.director);
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stats(shots: u32, hits: u32, damage_taken: f32, secs: f32) -> WaveStats {
+        let mut world = World::new();
+        let mut stats = WaveStats::default();
+        stats.shots = shots;
+        stats.hits = (0..hits).map(|_| world.spawn_empty().id()).collect();
+        stats.damage_taken = damage_taken;
+        stats.secs = secs;
+        stats
+    }
+
+    #[test]
+    fn a_par_wave_is_no_change() {
+        let rating = rate_wave(&stats(10, 5, PAR_DAMAGE / 2.0, PAR_CLEAR_SECS));
+        assert!(rating.abs() < 1e-6);
+    }
+
+    #[test]
+    fn a_perfect_wave_is_as_good_as_it_gets() {
+        assert_eq!(rate_wave(&stats(10, 10, 0.0, 0.0)), 1.0);
+    }
+
+    #[test]
+    fn a_dreadful_wave_is_as_bad_as_it_gets() {
+        assert_eq!(rate_wave(&stats(10, 0, PAR_DAMAGE * 3.0, PAR_CLEAR_SECS * 3.0)), -1.0);
+    }
+
+    #[test]
+    fn scale_lands_on_the_bounds() {
+        assert_eq!(scale(1.0, 0.7, 1.4), 1.4);
+        assert_eq!(scale(-1.0, 0.7, 1.4), 0.7);
+        assert_eq!(scale(0.0, 0.7, 1.4), 1.0);
+    }
+
+    #[test]
+    fn record_only_remembers_the_last_few_waves() {
+        let config = DirectorConfig {
+            waves_remembered: 2,
+            ..default()
+        };
+        let mut director = Director::default();
+
+        director.record(-1.0, &config);
+        director.record(1.0, &config);
+        director.record(1.0, &config);
+
+        assert_eq!(director.recent, [1.0, 1.0]);
+        assert_eq!(director.skill, 1.0);
+        assert_eq!(director.asteroid_speed, config.asteroid_speed_max);
+        // Doing well makes the saucers better shots.
+        assert_eq!(director.saucer_aim_error, config.saucer_aim_error_min);
+    }
+}
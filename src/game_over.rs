@@ -6,31 +6,61 @@ use bevy::{
 
 use crate::{
     sound::ambient::ThrusterSound,
+    app_globals::AppGlobals,
     asset_loader::SceneAssets,
+    difficulty::Difficulty,
+    modes::GameMode,
+    ore::{OreKind, RunOreStats},
+    players::PlayerCount,
     saucer::SaucerSpawnTimer,
-    state::GameState,
+    spaceship::controls::{PLAYER_1_KEYS_HELP, PLAYER_2_KEYS_HELP},
+    state::{GameSetup, GameState},
 };
 
 #[derive(Component, Debug)]
 pub struct GameOverDlg;
 
+#[derive(Component, Debug)]
+pub struct GameOverOreText;
+
+#[derive(Component, Debug)]
+pub struct GameOverModeText;
+
+#[derive(Component, Debug)]
+pub struct GameOverDifficultyText;
+
+#[derive(Component, Debug)]
+pub struct GameOverPlayersText;
+
+#[derive(Component, Debug)]
+pub struct GameOverLeaderboardText;
+
 
 pub struct GameOverPlugin;
 
 impl Plugin for GameOverPlugin {
     fn build(&self, app: &mut App) {
         app
-        .add_systems(PostStartup, spawn_game_over_dlg)
+        .add_systems(GameSetup, spawn_game_over_dlg)
         .add_systems(OnEnter(GameState::GameOver), (
             show_game_over_dlg,
+            show_run_ore_stats,
             mute_thruster_sound,
         ))
         .add_systems(OnEnter(GameState::StartGame), (
             hide_game_over_dlg,
             reset_saucer_spawn_timer,
         ))
-        .add_systems(Update,
-            quit_or_start_new_game.run_if(in_state(GameState::GameOver)),
+        .add_systems(Update, (
+                quit_or_start_new_game,
+                choose_mode,
+                choose_difficulty,
+                choose_players,
+                show_mode,
+                show_difficulty,
+                show_players,
+                show_leaderboard,
+            ).run_if(in_state(GameState::GameOver)),
         )
         .add_systems(OnEnter(GameState::QuitGame),
         quit_game
@@ -69,12 +99,96 @@ fn spawn_game_over_dlg(
             Text::new("Game Over!"),
             // "default_font" feature is unavailable, load a font to use instead.
             TextFont { 
-                font: scene_assets.font.clone(),
+                font: scene_assets.font("font"),
                 font_size: 44.0,
                 ..Default::default()
             },
         ));
 
+        builder.spawn((
+            Node {
+                padding: UiRect::axes(Val::Px(5.), Val::Px(1.)),
+                ..default()
+            },
+        ))
+        .with_child((
+            Text::new(""),
+            TextFont { 
+                font: scene_assets.font("font"),
+                font_size: 18.0,
+                ..Default::default()
+            },
+            TextColor(Color::srgb(1.0, 0.8, 0.1)),
+            GameOverOreText,
+        ));
+
+        builder.spawn((
+            Node {
+                padding: UiRect::axes(Val::Px(5.), Val::Px(1.)),
+                ..default()
+            },
+        ))
+        .with_child((
+            Text::new(""),
+            TextFont { 
+                font: scene_assets.font("font"),
+                font_size: 22.0,
+                ..Default::default()
+            },
+            TextLayout::new_with_justify(Justify::Center),
+            GameOverModeText,
+        ));
+
+        builder.spawn((
+            Node {
+                padding: UiRect::axes(Val::Px(5.), Val::Px(1.)),
+                ..default()
+            },
+        ))
+        .with_child((
+            Text::new(""),
+            TextFont { 
+                font: scene_assets.font("font"),
+                font_size: 22.0,
+                ..Default::default()
+            },
+            GameOverDifficultyText,
+        ));
+
+        builder.spawn((
+            Node {
+                padding: UiRect::axes(Val::Px(5.), Val::Px(1.)),
+                ..default()
+            },
+        ))
+        .with_child((
+            Text::new(""),
+            TextFont { 
+                font: scene_assets.font("font"),
+                font_size: 22.0,
+                ..Default::default()
+            },
+            TextLayout::new_with_justify(Justify::Center),
+            GameOverPlayersText,
+        ));
+
+        builder.spawn((
+            Node {
+                padding: UiRect::axes(Val::Px(5.), Val::Px(1.)),
+                ..default()
+            },
+        ))
+        .with_child((
+            Text::new(""),
+            TextFont { 
+                font: scene_assets.font("font"),
+                font_size: 18.0,
+                ..Default::default()
+            },
+            TextColor(Color::srgb(0.6, 0.9, 1.0)),
+            GameOverLeaderboardText,
+        ));
+
         builder.spawn((
             Node {
                 padding: UiRect::axes(Val::Px(5.), Val::Px(1.)),
@@ -85,7 +199,7 @@ fn spawn_game_over_dlg(
         .with_child((
             Text::new("Press <Enter> to start new game."),
             TextFont { 
-                font: scene_assets.font.clone(),
+                font: scene_assets.font("font"),
                 font_size: 22.0,
                 ..Default::default()
             },
@@ -101,7 +215,7 @@ fn spawn_game_over_dlg(
         .with_child((
             Text::new("Press Q to quit."),
             TextFont { 
-                font: scene_assets.font.clone(),
+                font: scene_assets.font("font"),
                 font_size: 22.0,
                 ..Default::default()
             },
@@ -110,7 +224,7 @@ fn spawn_game_over_dlg(
     });
 }
 
-fn mute_thruster_sound(
+pub fn mute_thruster_sound(
     mut thruster_audio: Query<&mut AudioSink, With<ThrusterSound>>,
 ) {
     // Quite often the player is trying to dodge an asteroid and gets killed.
@@ -130,6 +244,20 @@ fn show_game_over_dlg(
     game_over_dlg.top = Val::Percent(0.0);
 }
 
+// Tell the player how much ore they hauled in this time around.
+fn show_run_ore_stats(
+    run_stats: Res<RunOreStats>,
+    mut ore_text: Single<&mut Text, With<GameOverOreText>>,
+) {
+    let breakdown = OreKind::ALL
+        .iter()
+        .map(|kind| format!("{} {}", run_stats.collected(*kind), kind.name()))
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    ore_text.0 = format!("Ore collected: {} credits ({})", run_stats.credits, breakdown);
+}
+
 // Hide the game over dialog.
 // We do this when we start a new game.
 fn hide_game_over_dlg(
@@ -159,6 +287,97 @@ fn quit_or_start_new_game(
     }
 }
 
+// The difficulty can only be changed between games, so a high score is
+// always for one difficulty the whole way through.
+fn choose_difficulty(
+    mut difficulty: ResMut<Difficulty>,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+) {
+    if keyboard_input.just_pressed(KeyCode::ArrowRight) {
+        difficulty.level = difficulty.level.next();
+    }
+    else if keyboard_input.just_pressed(KeyCode::ArrowLeft) {
+        difficulty.level = difficulty.level.prev();
+    }
+}
+
+// Same as the difficulty, each mode keeps its own scores.
+fn choose_mode(
+    mut mode: ResMut<GameMode>,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+) {
+    if keyboard_input.just_pressed(KeyCode::ArrowDown) {
+        *mode = mode.next();
+    }
+    else if keyboard_input.just_pressed(KeyCode::ArrowUp) {
+        *mode = mode.prev();
+    }
+}
+
+// One player, or two sharing the keyboard (and any gamepads).
+fn choose_players(
+    mut player_count: ResMut<PlayerCount>,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+) {
+    if keyboard_input.just_pressed(KeyCode::KeyP) {
+        *player_count = player_count.next();
+    }
+}
+
+fn show_mode(
+    mode: Res<GameMode>,
+    mut mode_text: Single<&mut Text, With<GameOverModeText>>,
+) {
+    mode_text.0 = format!(
+        "Mode: < {} >  (<Up>/<Down> to change)\n{}",
+        mode.name(),
+        mode.description(),
+    );
+}
+
+fn show_difficulty(
+    difficulty: Res<Difficulty>,
+    mut difficulty_text: Single<&mut Text, With<GameOverDifficultyText>>,
+) {
+    difficulty_text.0 = format!("Difficulty: < {} >  (<Left>/<Right> to change)", difficulty.level.name());
+}
+
+// In co-op, remind everyone which keys are theirs.
+fn show_players(
+    player_count: Res<PlayerCount>,
+    mut players_text: Single<&mut Text, With<GameOverPlayersText>>,
+) {
+    players_text.0 = format!("Players: < {} >  (<P> to change)", player_count.0);
+
+    if player_count.is_coop() {
+        players_text.0 += &format!("\n{}\n{}", PLAYER_1_KEYS_HELP, PLAYER_2_KEYS_HELP);
+    }
+}
+
+// The best scores for whatever mode and difficulty are picked right now.
+fn show_leaderboard(
+    app_globals: Res<AppGlobals>,
+    mode: Res<GameMode>,
+    difficulty: Res<Difficulty>,
+    mut leaderboard_text: Single<&mut Text, With<GameOverLeaderboardText>>,
+) {
+    let scores = app_globals.leaderboard((*mode, difficulty.level));
+
+    leaderboard_text.0 = if scores.is_empty() {
+        "No scores yet".to_string()
+    }
+    else {
+        let ranks = scores
+            .iter()
+            .enumerate()
+            .map(|(i, score)| format!("{}. {}", i + 1, score))
+            .collect::<Vec<_>>()
+            .join("   ");
+
+        format!("Best: {}", ranks)
+    };
+}
+
 fn quit_game(
     mut app_exit_events: ResMut<Messages<AppExit>>
 ) {
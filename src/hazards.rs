@@ -0,0 +1,315 @@
+use std::f32::consts::{FRAC_PI_2, TAU};
+
+use bevy::prelude::*;
+
+use crate::{
+    asteroids::{levels::wave_definition, Asteroid, WaveStartedEvent},
+    boss::{Boss, BossHitZone},
+    health::Health,
+    movement::{play_area_bounds, random_play_area_position, Velocity},
+    schedule::InGameSet,
+    spaceship::{hyperspace::Warping, Spaceship},
+    state::GameState,
+};
+
+// Keep hazards away from the edges, and don't drop one right on top of the
+// ship at the start of a wave.
+const HAZARD_EDGE_MARGIN: f32 = 10.0;
+const HAZARD_SAFE_DISTANCE: f32 = 20.0;
+const HAZARD_PLACEMENT_TRIES: usize = 8;
+
+const DISTORTION_RINGS: usize = 4;
+const DISTORTION_CYCLE_SECS: f32 = 1.6;  // for a ring to go all the way in (or out)
+const DISTORTION_WOBBLE: f32 = 0.06;  // how far the rings warp out of round
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HazardKind {
+    GravityWell,
+    BlackHole,
+    RepulsorNebula,
+}
+
+impl HazardKind {
+    pub fn props(&self) -> &'static HazardProperties {
+        match self {
+            HazardKind::GravityWell => &GRAVITY_WELL,
+            HazardKind::BlackHole => &BLACK_HOLE,
+            HazardKind::RepulsorNebula => &REPULSOR_NEBULA,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct HazardProperties {
+    pub name: &'static str,
+    pub radius: f32,  // how far out the force reaches
+    pub strength: f32,  // acceleration at the centre, negative pushes away
+    pub core_radius: f32,  // anything inside this is swallowed, 0 for none
+    pub visual_radius: f32,
+    pub color: Color,
+}
+
+const GRAVITY_WELL: HazardProperties = HazardProperties {
+    name: "gravity_well",
+    radius: 25.0,
+    strength: 30.0,
+    core_radius: 0.0,
+    visual_radius: 1.5,
+    color: Color::srgba(0.4, 0.6, 1.0, 0.5),
+};
+
+const BLACK_HOLE: HazardProperties = HazardProperties {
+    name: "black_hole",
+    radius: 30.0,
+    strength: 45.0,
+    core_radius: 2.0,
+    visual_radius: 2.0,
+    color: Color::srgba(0.6, 0.3, 1.0, 0.8),
+};
+
+const REPULSOR_NEBULA: HazardProperties = HazardProperties {
+    name: "repulsor_nebula",
+    radius: 18.0,
+    strength: -35.0,
+    core_radius: 0.0,
+    visual_radius: 9.0,
+    color: Color::srgba(1.0, 0.3, 0.7, 0.25),
+};
+
+
+#[derive(Component, Debug)]
+pub struct Hazard {
+    pub kind: HazardKind,
+}
+
+#[derive(Resource, Debug)]
+pub struct HazardAssets {
+    mesh: Handle<Mesh>,
+    gravity_well: Handle<StandardMaterial>,
+    black_hole: Handle<StandardMaterial>,
+    repulsor_nebula: Handle<StandardMaterial>,
+}
+
+impl HazardAssets {
+    fn material(&self, kind: HazardKind) -> Handle<StandardMaterial> {
+        match kind {
+            HazardKind::GravityWell => self.gravity_well.clone(),
+            HazardKind::BlackHole => self.black_hole.clone(),
+            HazardKind::RepulsorNebula => self.repulsor_nebula.clone(),
+        }
+    }
+}
+
+
+pub struct HazardPlugin;
+
+impl Plugin for HazardPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, create_hazard_assets)
+        .add_systems(Update, (
+                spawn_wave_hazards,
+                apply_hazard_forces,
+                swallow_into_black_holes,
+                draw_hazard_distortion,
+            ).in_set(InGameSet::EntityUpdates),
+        )
+        .add_systems(OnEnter(GameState::GameOver), despawn_all_hazards);
+    }
+}
+
+fn create_hazard_assets(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    let mut glow = |color: Color| materials.add(StandardMaterial {
+        base_color: color,
+        emissive: color.to_linear() * 2.0,
+        alpha_mode: AlphaMode::Blend,
+        unlit: true,
+        ..default()
+    });
+
+    let gravity_well = glow(GRAVITY_WELL.color);
+    let repulsor_nebula = glow(REPULSOR_NEBULA.color);
+
+    // Nothing gets out of a black hole, light included.
+    let black_hole = materials.add(StandardMaterial {
+        base_color: Color::BLACK,
+        unlit: true,
+        ..default()
+    });
+
+    commands.insert_resource(HazardAssets {
+        mesh: meshes.add(Sphere::new(1.0)),
+        gravity_well,
+        black_hole,
+        repulsor_nebula,
+    });
+}
+
+// Each wave clears away the last wave's hazards and brings its own.
+fn spawn_wave_hazards(
+    mut commands: Commands,
+    mut wave_started_reader: MessageReader<WaveStartedEvent>,
+    hazard_assets: Res<HazardAssets>,
+    hazards: Query<Entity, With<Hazard>>,
+    ship_q: Query<&Transform, With<Spaceship>>,
+    camera_query: Query<&Projection, With<Camera>>,
+) {
+    for event in wave_started_reader.read() {
+        for entity in hazards.iter() {
+            commands.entity(entity).despawn();
+        }
+
+        let wave = wave_definition(event.level);
+        if wave.hazards.is_empty() {
+            continue;
+        }
+
+        let Ok(projection) = camera_query.single() else { return; };
+        let (min, max) = play_area_bounds(projection);
+        // Keep clear of every ship, or of the middle if there aren't any.
+        let mut ship_positions = ship_q.iter().map(|xform| xform.translation).collect::<Vec<_>>();
+        if ship_positions.is_empty() {
+            ship_positions.push(Vec3::ZERO);
+        }
+
+        let mut rng = rand::rng();
+        let mut random_position = || random_play_area_position(&mut rng, min, max, HAZARD_EDGE_MARGIN);
+
+        for kind in wave.hazards {
+            let props = kind.props();
+
+            let mut position = random_position();
+            for _ in 0..HAZARD_PLACEMENT_TRIES {
+                let too_close = ship_positions.iter().any(|ship_position| {
+                    position.distance(*ship_position) < HAZARD_SAFE_DISTANCE
+                });
+                if !too_close {
+                    break;
+                }
+                position = random_position();
+            }
+
+            // Nebulae are big flat clouds, the others are little balls.
+            let scale = if *kind == HazardKind::RepulsorNebula {
+                Vec3::new(props.visual_radius, 0.2, props.visual_radius)
+            }
+            else {
+                Vec3::splat(props.visual_radius)
+            };
+
+            commands.spawn((
+                Name::new(props.name),
+                Hazard { kind: *kind },
+                Mesh3d(hazard_assets.mesh.clone()),
+                MeshMaterial3d(hazard_assets.material(*kind)),
+                Transform::from_translation(position).with_scale(scale),
+            ));
+
+            #[cfg(debug_assertions)]
+            info!("Spawned {:?} at {:?}", kind, position);
+        }
+    }
+}
+
+// Pulls (or pushes) on anything that moves, stronger the closer it gets.
+fn apply_hazard_forces(
+    hazards: Query<(&Hazard, &Transform)>,
+    mut movers: Query<(&Transform, &mut Velocity), Without<Hazard>>,
+    time: Res<Time>,
+) {
+    for (hazard, hazard_xform) in hazards.iter() {
+        let props = hazard.kind.props();
+
+        for (xform, mut velocity) in movers.iter_mut() {
+            let offset = (hazard_xform.translation - xform.translation).with_y(0.0);
+            let distance = offset.length();
+            if distance >= props.radius || distance < f32::EPSILON {
+                continue;
+            }
+
+            let falloff = 1.0 - distance / props.radius;
+            velocity.value += offset / distance * props.strength * falloff * time.delta_secs();
+        }
+    }
+}
+
+// A ship that is warping isn't really there, and can't be hit either.  The
+// mothership is far too big to be swallowed, so it is left alone.
+type Swallowable<'w, 's> = Query<
+    'w,
+    's,
+    (Entity, &'static Transform, Option<&'static mut Health>, Has<Asteroid>),
+    (With<Velocity>, Without<Hazard>, Without<Warping>, Without<Boss>, Without<BossHitZone>),
+>;
+
+fn swallow_into_black_holes(
+    mut commands: Commands,
+    hazards: Query<(&Hazard, &Transform)>,
+    mut movers: Swallowable,
+) {
+    for (hazard, hazard_xform) in hazards.iter() {
+        let props = hazard.kind.props();
+        if props.core_radius <= 0.0 {
+            continue;
+        }
+
+        for (entity, xform, health, is_asteroid) in movers.iter_mut() {
+            if xform.translation.distance(hazard_xform.translation) >= props.core_radius {
+                continue;
+            }
+
+            // Asteroids just disappear, rather than breaking up and scoring
+            // points.  The ship (and saucers) are destroyed the usual way,
+            // so that everything that goes with that still happens.
+            match health {
+                Some(mut health) if !is_asteroid => health.value = 0.0,
+                _ => commands.entity(entity).try_despawn(),
+            }
+        }
+    }
+}
+
+// There's no actual lensing going on, just rings that warp and flow in
+// towards the centre (or out of it, for a repulsor) to suggest space being
+// bent out of shape.
+fn draw_hazard_distortion(
+    mut gizmos: Gizmos,
+    hazards: Query<(&Hazard, &Transform)>,
+    time: Res<Time>,
+) {
+    let flat = Quat::from_rotation_x(FRAC_PI_2);
+    let elapsed = time.elapsed_secs();
+
+    for (hazard, xform) in hazards.iter() {
+        let props = hazard.kind.props();
+        let inward = props.strength > 0.0;
+
+        for ring in 0..DISTORTION_RINGS {
+            let phase = (elapsed / DISTORTION_CYCLE_SECS + ring as f32 / DISTORTION_RINGS as f32).fract();
+            let t = if inward { 1.0 - phase } else { phase };
+
+            let wobble = 1.0 + DISTORTION_WOBBLE * (elapsed * 3.0 + ring as f32 * TAU / 3.0).sin();
+            let radius = (props.core_radius.max(props.visual_radius * 0.5) +
+                          (props.radius - props.visual_radius * 0.5) * t) * wobble;
+
+            // Fade in from the outside and out towards the middle.
+            let alpha = props.color.alpha() * (1.0 - t) * t * 4.0;
+            let isometry = Isometry3d::new(xform.translation, flat * Quat::from_rotation_z(elapsed * 0.5));
+
+            gizmos.ellipse(isometry, Vec2::new(radius, radius / wobble), props.color.with_alpha(alpha));
+        }
+    }
+}
+
+fn despawn_all_hazards(
+    mut commands: Commands,
+    query: Query<Entity, With<Hazard>>,
+) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn();
+    }
+}
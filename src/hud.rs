@@ -3,54 +3,154 @@ use bevy::prelude::*;
 use crate::{
     asset_loader::SceneAssets,
     health::Health,
+    boss::{Boss, BossHitZone},
+    ore::OreWallet,
+    pickups::ShieldOvercharge,
+    players::{Player, PlayerCount, Players, MAX_PLAYERS},
     spaceship::{
         shield::Shield,
+        hyperspace::{HyperspaceController, HyperspaceState},
+        upgrades::ShipStats,
+        weapons::{Weapon, WeaponKind},
         ShieldController,
         ShieldState,
         Spaceship,
     },
     schedule::InGameSet,
+    state::GameSetup,
 };
 
-const SHIP_HP_MAX: f32 = 100.0;   // keep in sync with SPACESHIP_HEALTH
-const SHIELD_HP_MAX: f32 = 60.0;  // keep in sync with SHIELD_HP
+// Each player has their own panel.  The second one is only shown in co-op.
+#[derive(Component, Debug)]
+pub struct HudPanel(pub Player);
+
+// Score and lives, which are only worth showing in co-op.  On your own the
+// score is up in the corner as always.
+#[derive(Component, Debug)]
+pub struct HudPlayerRow;
+
+#[derive(Component, Debug)]
+pub struct PlayerStatusText(pub Player);
 
 #[derive(Component, Debug)]
-pub struct ShipBarFill;
+pub struct ShipBarFill(pub Player);
 
 #[derive(Component, Debug)]
-pub struct ShieldBarFill;
+pub struct ShieldBarFill(pub Player);
+
+#[derive(Component, Debug)]
+pub struct HyperspaceIndicator(pub Player);
+
+#[derive(Component, Debug)]
+pub struct OreText;
+
+#[derive(Component, Debug)]
+pub struct WeaponText(pub Player);
+
+#[derive(Component, Debug)]
+pub struct BossBar;
+
+#[derive(Component, Debug)]
+pub struct BossBarFill;
 
 pub struct HudPlugin;
 
 impl Plugin for HudPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(PostStartup, spawn_hud_bars);
+        app.add_systems(GameSetup, (spawn_hud_bars, spawn_boss_bar));
         app.add_systems(
             Update,
-            update_hud_bars.in_set(InGameSet::EntityUpdates),
+            (
+                update_hud_bars,
+                update_hud_weapon,
+                update_hud_hyperspace,
+                update_hud_players,
+                update_boss_bar,
+            ).in_set(InGameSet::EntityUpdates),
+        );
+        app.add_systems(
+            Update,
+            (
+                update_hud_ore.run_if(resource_changed::<OreWallet>),
+                show_hud_panels.run_if(resource_changed::<PlayerCount>),
+            ),
         );
     }
 }
 
-fn spawn_hud_bars(mut commands: Commands, scene_assets: Res<SceneAssets>) {
+fn spawn_hud_bars(
+    mut commands: Commands,
+    scene_assets: Res<SceneAssets>,
+    player_count: Res<PlayerCount>,
+) {
+    for player in (0..MAX_PLAYERS).map(Player) {
+        spawn_hud_panel(&mut commands, &scene_assets, player, *player_count);
+    }
+}
+
+fn spawn_hud_panel(
+    commands: &mut Commands,
+    scene_assets: &SceneAssets,
+    player: Player,
+    player_count: PlayerCount,
+) {
     // Layout constants (tune later)
     let bar_w = 100.0;
     let bar_h = 6.0;
     let gap_y = 2.0;
 
-    // Root container (top-left)
-    commands
-        .spawn(Node {
-            position_type: PositionType::Absolute,
-            left: Val::Px(150.0),
-            top: Val::Px(16.0),
-            display: Display::Flex,
-            flex_direction: FlexDirection::Column,
-            row_gap: Val::Px(gap_y),
+    let label = |text: &str| (
+        Text::new(text),
+        TextFont {
+            font: scene_assets.font("font"),
+            font_size: 14.0,
             ..default()
-        })
+        },
+    );
+
+    // Root container.  Player 1's is top-left, player 2's top-right, under
+    // the last score.
+    let (left, right, top) = if player.0 == 0 {
+        (Val::Px(150.0), Val::Auto, Val::Px(16.0))
+    }
+    else {
+        (Val::Auto, Val::Percent(2.0), Val::Percent(8.0))
+    };
+
+    commands
+        .spawn((
+            Name::new(format!("hud_panel_{}", player.name())),
+            Node {
+                position_type: PositionType::Absolute,
+                left,
+                right,
+                top,
+                display: panel_display(player, player_count),
+                flex_direction: FlexDirection::Column,
+                row_gap: Val::Px(gap_y),
+                ..default()
+            },
+            HudPanel(player),
+        ))
         .with_children(|root| {
+            // Player row
+            root.spawn((
+                Node {
+                    display: if player_count.is_coop() { Display::Flex } else { Display::None },
+                    align_items: AlignItems::Center,
+                    column_gap: Val::Px(8.0),
+                    ..default()
+                },
+                HudPlayerRow,
+            ))
+            .with_children(|row| {
+                row.spawn(label(&player.name()));
+                row.spawn((
+                    label(""),
+                    PlayerStatusText(player),
+                ));
+            });
+
             // Ship row
             root.spawn(Node {
                 display: Display::Flex,
@@ -59,14 +159,7 @@ fn spawn_hud_bars(mut commands: Commands, scene_assets: Res<SceneAssets>) {
                 ..default()
             })
             .with_children(|row| {
-                row.spawn((
-                    Text::new("SHIP"),
-                    TextFont {
-                        font: scene_assets.font.clone(),
-                        font_size: 14.0,
-                        ..default()
-                    },
-                ));
+                row.spawn(label("SHIP"));
                 // Bar frame
                 row.spawn((
                     Node {
@@ -85,7 +178,7 @@ fn spawn_hud_bars(mut commands: Commands, scene_assets: Res<SceneAssets>) {
                             ..default()
                         },
                         BackgroundColor(Color::srgba(0.2, 1.0, 0.2, 0.9)), // green-ish
-                        ShipBarFill,
+                        ShipBarFill(player),
                     ));
                 });
             });
@@ -98,14 +191,7 @@ fn spawn_hud_bars(mut commands: Commands, scene_assets: Res<SceneAssets>) {
                 ..default()
             })
             .with_children(|row| {
-                row.spawn((
-                    Text::new("SHLD"),
-                    TextFont {
-                        font: scene_assets.font.clone(),
-                        font_size: 14.0,
-                        ..default()
-                    },
-                ));
+                row.spawn(label("SHLD"));
 
                 // Bar frame
                 row.spawn((
@@ -125,61 +211,316 @@ fn spawn_hud_bars(mut commands: Commands, scene_assets: Res<SceneAssets>) {
                             ..default()
                         },
                         BackgroundColor(Color::srgba(0.3, 0.8, 1.0, 0.9)), // cyan/blue-ish
-                        ShieldBarFill,
+                        ShieldBarFill(player),
                     ));
                 });
+
+                // Hyperspace indicator
+                row.spawn((
+                    label("HYP"),
+                    HyperspaceIndicator(player),
+                ));
+            });
+
+            // Weapon row
+            root.spawn(Node {
+                display: Display::Flex,
+                align_items: AlignItems::Center,
+                column_gap: Val::Px(8.0),
+                ..default()
+            })
+            .with_children(|row| {
+                row.spawn(label("WPN"));
+                row.spawn((
+                    label(""),
+                    WeaponText(player),
+                ));
+            });
+
+            // Ore row.  The ore is shared, so it only needs showing once.
+            if player.0 != 0 {
+                return;
+            }
+
+            root.spawn(Node {
+                display: Display::Flex,
+                align_items: AlignItems::Center,
+                column_gap: Val::Px(8.0),
+                ..default()
+            })
+            .with_children(|row| {
+                row.spawn(label("ORE"));
+                row.spawn((
+                    label("0"),
+                    TextColor(Color::srgb(1.0, 0.8, 0.1)),
+                    OreText,
+                ));
             });
         });
 }
 
+fn panel_display(player: Player, player_count: PlayerCount) -> Display {
+    if player.0 < player_count.0 {
+        Display::Flex
+    }
+    else {
+        Display::None
+    }
+}
+
+fn show_hud_panels(
+    mut panel_q: Query<(&HudPanel, &mut Node), Without<HudPlayerRow>>,
+    mut row_q: Query<&mut Node, (With<HudPlayerRow>, Without<HudPanel>)>,
+    player_count: Res<PlayerCount>,
+) {
+    for (panel, mut node) in panel_q.iter_mut() {
+        node.display = panel_display(panel.0, *player_count);
+    }
+
+    for mut node in row_q.iter_mut() {
+        node.display = if player_count.is_coop() { Display::Flex } else { Display::None };
+    }
+}
+
+// Their score, and either how many ships they have left or how long until
+// the next one turns up.
+fn update_hud_players(
+    players: Res<Players>,
+    mut status_q: Query<(&PlayerStatusText, &mut Text)>,
+) {
+    for (PlayerStatusText(player), mut text) in status_q.iter_mut() {
+        let state = players.get(*player);
+
+        let status = match &state.respawn {
+            Some(respawn) => format!("next ship in {:.0}", respawn.remaining_secs().ceil()),
+            None if state.lives == 0 => "OUT".to_string(),
+            None => format!("Lives {}", state.lives),
+        };
+
+        text.0 = format!("{}  {}", state.score, status);
+    }
+}
+
+// What the bars need to know about each ship.
+type ShipBarStats = (
+    Entity,
+    &'static Player,
+    &'static Health,
+    &'static ShieldController,
+    &'static ShipStats,
+    Has<ShieldOvercharge>,
+);
 
 fn update_hud_bars(
-    ship_q: Query<(Entity, &Health, &ShieldController), With<Spaceship>>,
+    ship_q: Query<ShipBarStats, With<Spaceship>>,
     shield_q: Query<(&Health, &Shield)>,
-    mut ship_fill_q: Query<&mut Node, (With<ShipBarFill>, Without<ShieldBarFill>)>,
+    mut ship_fill_q: Query<(&ShipBarFill, &mut Node), Without<ShieldBarFill>>,
     mut shield_fill_q: Query<
-        (&mut Node, &mut BackgroundColor),
-        (With<ShieldBarFill>, Without<ShipBarFill>),
+        (&ShieldBarFill, &mut Node, &mut BackgroundColor),
+        Without<ShipBarFill>,
     >,
 ) {
-    // If ship is gone → empty both bars and bail.
-    let Ok((ship_e, ship_health, controller)) = ship_q.single() else {
-        if let Ok(mut ship_fill) = ship_fill_q.single_mut() {
+    for (ShipBarFill(player), mut ship_fill) in ship_fill_q.iter_mut() {
+        // If ship is gone → empty both bars and move on.
+        let Some((ship_e, _, ship_health, controller, stats, overcharged)) =
+            ship_q.iter().find(|(_, p, ..)| *p == player)
+        else {
             ship_fill.width = Val::Px(0.0);
+            for (_, mut shield_fill, _) in shield_fill_q.iter_mut().filter(|(fill, ..)| fill.0 == *player) {
+                shield_fill.width = Val::Px(0.0);
+            }
+            continue;
+        };
+
+        // --- Ship bar ---
+        let t_ship = (ship_health.value / stats.max_health).clamp(0.0, 1.0);
+        ship_fill.width = Val::Percent(t_ship * 100.0);
+
+        // --- Shield bar ---
+        let Some((_, mut shield_fill, mut shield_color)) =
+            shield_fill_q.iter_mut().find(|(fill, ..)| fill.0 == *player)
+        else {
+            continue;
+        };
+
+        // While the shield is down the bar shows how much charge is banked.
+        let t_charge = (controller.charge / stats.shield_hp).clamp(0.0, 1.0);
+
+        match controller.state {
+            ShieldState::Active => {
+                let color = if overcharged {
+                    Color::srgba(1.0, 0.85, 0.3, 0.9)
+                }
+                else {
+                    Color::srgba(0.3, 0.8, 1.0, 0.9)
+                };
+
+                // find shield that belongs to this ship
+                if let Some((shield_health, shield)) = shield_q.iter().find(|(_, s)| s.ship == ship_e) {
+                    let t = (shield_health.value / shield.max_hp).clamp(0.0, 1.0);
+                    shield_fill.width = Val::Percent(t * 100.0);
+                } else {
+                    shield_fill.width = Val::Percent(0.0);
+                }
+                *shield_color = BackgroundColor(color);
+            }
+            ShieldState::Recharging => {
+                shield_fill.width = Val::Percent(t_charge * 100.0);
+                *shield_color = BackgroundColor(Color::srgba(0.3, 0.8, 1.0, 0.35));
+            }
+            ShieldState::Ready => {
+                shield_fill.width = Val::Percent(t_charge * 100.0);
+                *shield_color = BackgroundColor(Color::srgba(0.3, 0.8, 1.0, 0.75));
+            }
         }
-        if let Ok((mut shield_fill, _color)) = shield_fill_q.single_mut() {
-            shield_fill.width = Val::Px(0.0);
-        }
-        return;
+    }
+}
+
+fn update_hud_ore(
+    wallet: Res<OreWallet>,
+    mut ore_text_q: Query<&mut Text, With<OreText>>,
+) {
+    for mut text in ore_text_q.iter_mut() {
+        text.0 = wallet.credits.to_string();
+    }
+}
+
+fn update_hud_weapon(
+    ship_q: Query<(&Player, &Weapon), With<Spaceship>>,
+    mut weapon_text_q: Query<(&WeaponText, &mut Text, &mut TextColor)>,
+) {
+    for (WeaponText(player), mut text, mut color) in weapon_text_q.iter_mut() {
+        let Some((_, weapon)) = ship_q.iter().find(|(p, _)| *p == player) else { continue; };
+        update_weapon_text(weapon, &mut text, &mut color);
+    }
+}
+
+fn update_weapon_text(weapon: &Weapon, text: &mut Text, color: &mut TextColor) {
+    let kind = weapon.selected;
+    let props = kind.props();
+    let slot = weapon.slot(kind);
+
+    // Name of the weapon, plus whatever limits how much it can fire.
+    let status = if slot.overheated {
+        "OVERHEATED".to_string()
+    }
+    else if let (Some(ammo), Some(max)) = (slot.ammo, props.max_ammo) {
+        format!("{}/{}", ammo, max)
+    }
+    else if props.heat_per_shot > 0.0 {
+        format!("heat {:.0}%", slot.heat * 100.0)
+    }
+    else if kind == WeaponKind::Beam {
+        format!("charge {:.0}%", weapon.charge * 100.0)
+    }
+    else {
+        String::new()
     };
 
-    // --- Ship bar ---
-    let Ok(mut ship_fill) = ship_fill_q.single_mut() else { return; };
-    let t_ship = (ship_health.value / SHIP_HP_MAX).clamp(0.0, 1.0);
-    ship_fill.width = Val::Percent(t_ship * 100.0);
-
-    // --- Shield bar ---
-    let Ok((mut shield_fill, mut shield_color)) = shield_fill_q.single_mut() else { return; };
-
-    match controller.state {
-        ShieldState::Active => {
-            // find shield that belongs to this ship
-            if let Some((shield_health, _)) = shield_q.iter().find(|(_, s)| s.ship == ship_e) {
-                let t = (shield_health.value / SHIELD_HP_MAX).clamp(0.0, 1.0);
-                shield_fill.width = Val::Percent(t * 100.0);
-                *shield_color = BackgroundColor(Color::srgba(0.3, 0.8, 1.0, 0.9));
-            } else {
-                shield_fill.width = Val::Percent(0.0);
-                *shield_color = BackgroundColor(Color::srgba(0.3, 0.8, 1.0, 0.9));
+    text.0 = format!("{} {}", props.name, status);
+    color.0 = if slot.overheated {
+        Color::srgb(1.0, 0.3, 0.2)
+    }
+    else {
+        Color::WHITE
+    };
+}
+
+fn update_hud_hyperspace(
+    ship_q: Query<(&Player, &HyperspaceController), With<Spaceship>>,
+    mut indicator_q: Query<(&HyperspaceIndicator, &mut Text, &mut TextColor)>,
+) {
+    for (HyperspaceIndicator(player), mut text, mut color) in indicator_q.iter_mut() {
+        let Some((_, controller)) = ship_q.iter().find(|(p, _)| *p == player) else { continue; };
+
+        match controller.state {
+            HyperspaceState::Ready => {
+                text.0 = "HYP".to_string();
+                color.0 = Color::srgb(0.2, 1.0, 0.2);
+            }
+            HyperspaceState::WarpOut | HyperspaceState::WarpIn => {
+                text.0 = "HYP".to_string();
+                color.0 = Color::srgb(0.5, 0.8, 1.0);
+            }
+            HyperspaceState::Cooldown => {
+                text.0 = format!("HYP {:.0}", controller.cooldown.remaining_secs().ceil());
+                color.0 = Color::srgba(1.0, 1.0, 1.0, 0.35);
             }
         }
-        ShieldState::Cooldown => {
-            shield_fill.width = Val::Percent(0.0);
-            *shield_color = BackgroundColor(Color::srgba(0.3, 0.8, 1.0, 0.35));
-        }
-        ShieldState::Ready => {
-            shield_fill.width = Val::Percent(100.0);
-            *shield_color = BackgroundColor(Color::srgba(0.3, 0.8, 1.0, 0.75));
-        }
     }
-}
\ No newline at end of file
+}
+
+// Only shown while there is a boss about.
+fn spawn_boss_bar(mut commands: Commands, scene_assets: Res<SceneAssets>) {
+    commands
+        .spawn((
+            Name::new("boss_bar"),
+            Node {
+                position_type: PositionType::Absolute,
+                top: Val::Percent(9.0),
+                width: Val::Percent(100.0),
+                display: Display::None,
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                column_gap: Val::Px(8.0),
+                ..default()
+            },
+            BossBar,
+        ))
+        .with_children(|row| {
+            row.spawn((
+                Text::new("BOSS"),
+                TextFont {
+                    font: scene_assets.font("font"),
+                    font_size: 14.0,
+                    ..default()
+                },
+            ));
+            row.spawn((
+                Node {
+                    width: Val::Px(300.0),
+                    height: Val::Px(8.0),
+                    ..default()
+                },
+                BackgroundColor(Color::srgba(1.0, 1.0, 1.0, 0.15)),
+            ))
+            .with_children(|frame| {
+                frame.spawn((
+                    Node {
+                        width: Val::Percent(100.0),
+                        height: Val::Percent(100.0),
+                        ..default()
+                    },
+                    BackgroundColor(Color::srgba(1.0, 0.25, 0.2, 0.9)),
+                    BossBarFill,
+                ));
+            });
+        });
+}
+
+fn update_boss_bar(
+    boss_q: Query<(Entity, &Boss)>,
+    zone_q: Query<(&BossHitZone, Option<&Health>)>,
+    mut bar_q: Query<&mut Node, (With<BossBar>, Without<BossBarFill>)>,
+    mut fill_q: Query<&mut Node, (With<BossBarFill>, Without<BossBar>)>,
+) {
+    let Ok(mut bar) = bar_q.single_mut() else { return; };
+
+    let Ok((boss_e, boss)) = boss_q.single() else {
+        bar.display = Display::None;
+        return;
+    };
+    bar.display = Display::Flex;
+
+    // The core counts as full health while it is still armored.
+    let health: f32 = zone_q
+        .iter()
+        .filter(|(zone, _)| zone.boss == boss_e)
+        .map(|(zone, health)| health.map_or(zone.max_health, |h| h.value.max(0.0)))
+        .sum();
+
+    if let Ok(mut fill) = fill_q.single_mut() {
+        fill.width = Val::Percent((health / boss.max_health).clamp(0.0, 1.0) * 100.0);
+    }
+}
+
@@ -0,0 +1,98 @@
+use bevy::prelude::*;
+
+use crate::{
+    health::Health,
+    movement::Velocity,
+    schedule::InGameSet,
+    state::GameState,
+};
+
+// Anything short-lived (missiles, debris, explosions, ...) gets one or both
+// of these, so that it gets cleaned up no matter where it ends up.
+
+// Despawned once the timer runs out.
+#[derive(Component, Debug)]
+pub struct Lifetime {
+    pub timer: Timer,
+}
+
+impl Lifetime {
+    pub fn new(secs: f32) -> Self {
+        Self {
+            timer: Timer::from_seconds(secs, TimerMode::Once),
+        }
+    }
+}
+
+// Despawned once it has travelled this far.
+#[derive(Component, Debug)]
+pub struct MaxRange {
+    pub remaining: f32,
+}
+
+impl MaxRange {
+    pub fn new(distance: f32) -> Self {
+        Self { remaining: distance }
+    }
+}
+
+
+pub struct LifetimePlugin;
+
+impl Plugin for LifetimePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, (
+                tick_lifetimes,
+                track_range,
+            ).in_set(InGameSet::EntityUpdates),
+        )
+        .add_systems(Update,
+            despawn_expired_entities.in_set(InGameSet::DespawnEntities),
+        )
+        .add_systems(OnEnter(GameState::GameOver), despawn_all_short_lived_entities);
+    }
+}
+
+fn tick_lifetimes(
+    mut query: Query<&mut Lifetime>,
+    time: Res<Time>,
+) {
+    for mut lifetime in query.iter_mut() {
+        lifetime.timer.tick(time.delta());
+    }
+}
+
+fn track_range(
+    mut query: Query<(&mut MaxRange, &Velocity)>,
+    time: Res<Time>,
+) {
+    for (mut range, velocity) in query.iter_mut() {
+        range.remaining -= velocity.value.length() * time.delta_secs();
+    }
+}
+
+type ShortLived = Or<(With<Lifetime>, With<MaxRange>)>;
+
+fn despawn_expired_entities(
+    mut commands: Commands,
+    query: Query<(Entity, Option<&Lifetime>, Option<&MaxRange>), ShortLived>,
+) {
+    for (entity, lifetime, range) in query.iter() {
+        let expired = lifetime.is_some_and(|l| l.timer.is_finished()) ||
+                      range.is_some_and(|r| r.remaining <= 0.0);
+
+        if expired {
+            commands.entity(entity).despawn();
+        }
+    }
+}
+
+// Anything with Health is already taken care of by the DespawnPlugin.
+fn despawn_all_short_lived_entities(
+    mut commands: Commands,
+    query: Query<Entity, (ShortLived, Without<Health>)>,
+) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn();
+    }
+}
@@ -0,0 +1,306 @@
+use bevy::{
+    asset::{LoadState, RecursiveDependencyLoadState, UntypedAssetId},
+    prelude::*,
+};
+
+use crate::{
+    asset_loader::{load_assets, AssetManifestHandle, SceneAssets},
+    state::GameState,
+};
+
+const PROGRESS_BAR_WIDTH: f32 = 400.0;
+const PROGRESS_BAR_HEIGHT: f32 = 12.0;
+
+
+#[derive(Component, Debug)]
+pub struct LoadingScreen;
+
+#[derive(Component, Debug)]
+pub struct LoadingBarFill;
+
+#[derive(Component, Debug)]
+pub struct LoadingFailedDlg;
+
+// Keeps track of how far along the asset loading is, and which assets
+// failed to load (name, path, error).
+#[derive(Resource, Debug, Default)]
+pub struct LoadingProgress {
+    pub loaded: usize,
+    pub total: usize,
+    pub failed: Vec<(String, String, String)>,
+    // Without the manifest we don't know about any assets at all, so
+    // there's nothing to play with.
+    pub manifest_failed: bool,
+}
+
+
+pub struct LoadingScreenPlugin;
+
+impl Plugin for LoadingScreenPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<LoadingProgress>()
+        .add_systems(OnEnter(GameState::Loading), spawn_loading_screen)
+        .add_systems(Update, (
+                check_asset_loading,
+                update_loading_bar,
+            )
+            .chain()
+            .after(load_assets)
+            .run_if(in_state(GameState::Loading)),
+        )
+        .add_systems(OnExit(GameState::Loading), despawn_loading_screen)
+        .add_systems(OnEnter(GameState::LoadingFailed), spawn_loading_failed_dlg)
+        .add_systems(Update,
+            continue_or_quit.run_if(in_state(GameState::LoadingFailed)),
+        )
+        .add_systems(OnExit(GameState::LoadingFailed), despawn_loading_failed_dlg);
+    }
+}
+
+fn spawn_loading_screen(mut commands: Commands) {
+    // The game font is one of the things we are waiting on, so the loading
+    // screen sticks to Bevy's built-in font.
+    commands.spawn((
+        Name::new("loading_screen"),
+        Node {
+            width: Val::Percent(100.0),
+            height: Val::Percent(100.0),
+            flex_direction: FlexDirection::Column,
+            align_items: AlignItems::Center,
+            justify_content: JustifyContent::Center,
+            row_gap: Val::Px(12.0),
+            ..default()
+        },
+        BackgroundColor(Color::linear_rgb(0.0005, 0.0, 0.005)),
+        GlobalZIndex(10),
+        LoadingScreen,
+    ))
+    .with_children(|builder| {
+        builder.spawn((
+            Text::new("Loading..."),
+            TextFont {
+                font_size: 33.0,
+                ..default()
+            },
+        ));
+
+        // Bar frame
+        builder.spawn((
+            Node {
+                width: Val::Px(PROGRESS_BAR_WIDTH),
+                height: Val::Px(PROGRESS_BAR_HEIGHT),
+                ..default()
+            },
+            BackgroundColor(Color::srgba(1.0, 1.0, 1.0, 0.15)),
+        ))
+        .with_children(|frame| {
+            // Fill (starts empty)
+            frame.spawn((
+                Node {
+                    width: Val::Percent(0.0),
+                    height: Val::Percent(100.0),
+                    ..default()
+                },
+                BackgroundColor(Color::srgba(0.3, 0.8, 1.0, 0.9)),
+                LoadingBarFill,
+            ));
+        });
+    });
+}
+
+fn check_asset_loading(
+    asset_server: Res<AssetServer>,
+    scene_assets: Res<SceneAssets>,
+    mut progress: ResMut<LoadingProgress>,
+    manifest_handle: Res<AssetManifestHandle>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    // Nothing else can start loading until we know what is in the manifest.
+    if let LoadState::Failed(err) = asset_server.load_state(&manifest_handle.handle) {
+        progress.failed = vec![(
+            String::from("manifest"),
+            asset_path_or_unknown(&asset_server, manifest_handle.handle.id().untyped()),
+            err.to_string(),
+        )];
+        progress.manifest_failed = true;
+        error!("Failed to load the asset manifest: {}", err);
+        next_state.set(GameState::LoadingFailed);
+        return;
+    }
+
+    if !scene_assets.is_populated {
+        return;
+    }
+
+    let handles = scene_assets.tracked_handles();
+
+    let mut loaded = 0;
+    let mut failed: Vec<(String, String, String)> = Vec::new();
+
+    for (name, id) in handles.iter() {
+        // A glTF scene is only usable once its meshes, materials and
+        // textures are loaded too, so we look at the whole dependency tree.
+        let error = match asset_server.load_state(*id) {
+            LoadState::Failed(err) => Some(err),
+            _ => match asset_server.recursive_dependency_load_state(*id) {
+                RecursiveDependencyLoadState::Failed(err) => Some(err),
+                RecursiveDependencyLoadState::Loaded => {
+                    loaded += 1;
+                    None
+                }
+                _ => None,
+            },
+        };
+
+        if let Some(err) = error {
+            let path = asset_path_or_unknown(&asset_server, *id);
+            failed.push((name.clone(), path, err.to_string()));
+        }
+    }
+
+    progress.loaded = loaded;
+    progress.total = handles.len();
+    progress.failed = failed;
+
+    if progress.loaded + progress.failed.len() < progress.total {
+        return;  // still waiting on something
+    }
+
+    if progress.failed.is_empty() {
+        info!("All {} assets loaded", progress.total);
+        next_state.set(GameState::InGame);
+    }
+    else {
+        for (name, path, err) in progress.failed.iter() {
+            error!("Failed to load asset '{}' ({}): {}", name, path, err);
+        }
+        next_state.set(GameState::LoadingFailed);
+    }
+}
+
+fn asset_path_or_unknown(asset_server: &AssetServer, id: UntypedAssetId) -> String {
+    asset_server
+        .get_path(id)
+        .map(|p| p.to_string())
+        .unwrap_or_else(|| String::from("<unknown path>"))
+}
+
+fn update_loading_bar(
+    progress: Res<LoadingProgress>,
+    mut fill: Single<&mut Node, With<LoadingBarFill>>,
+) {
+    if progress.total == 0 {
+        return;
+    }
+
+    let t = (progress.loaded as f32 / progress.total as f32).clamp(0.0, 1.0);
+    fill.width = Val::Percent(t * 100.0);
+}
+
+fn despawn_loading_screen(
+    mut commands: Commands,
+    query: Query<Entity, With<LoadingScreen>>,
+) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn();
+    }
+}
+
+fn spawn_loading_failed_dlg(
+    mut commands: Commands,
+    progress: Res<LoadingProgress>,
+) {
+    commands.spawn((
+        Name::new("loading_failed_dlg"),
+        Node {
+            width: Val::Percent(100.0),
+            height: Val::Percent(100.0),
+            flex_direction: FlexDirection::Column,
+            align_items: AlignItems::Center,
+            justify_content: JustifyContent::Center,
+            row_gap: Val::Px(6.0),
+            ..default()
+        },
+        BackgroundColor(Color::linear_rgb(0.0005, 0.0, 0.005)),
+        GlobalZIndex(10),
+        LoadingFailedDlg,
+    ))
+    .with_children(|builder| {
+        let (title, choices) = if progress.manifest_failed {
+            (
+                "The asset manifest failed to load:",
+                "Press R to retry, or Q to quit.",
+            )
+        }
+        else {
+            (
+                "Some assets failed to load:",
+                "Press <Enter> to play anyway, or Q to quit.",
+            )
+        };
+
+        builder.spawn((
+            Text::new(title),
+            TextFont {
+                font_size: 33.0,
+                ..default()
+            },
+            TextColor(Color::srgb(1.0, 0.3, 0.3)),
+        ));
+
+        for (name, path, err) in progress.failed.iter() {
+            builder.spawn((
+                Text::new(format!("{} ({}): {}", name, path, err)),
+                TextFont {
+                    font_size: 16.0,
+                    ..default()
+                },
+            ));
+        }
+
+        builder.spawn((
+            Node {
+                margin: UiRect::top(Val::Px(20.0)),
+                ..default()
+            },
+            Text::new(choices),
+            TextFont {
+                font_size: 22.0,
+                ..default()
+            },
+        ));
+    });
+}
+
+// Missing models will simply be invisible, and missing sounds silent, so
+// the game is still playable.  Let the player decide.  Without the manifest
+// there would be no ship, no font and no sound at all, so then the only
+// choices are to try again or give up.
+fn continue_or_quit(
+    mut game_state: ResMut<NextState<GameState>>,
+    mut progress: ResMut<LoadingProgress>,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+) {
+    if progress.manifest_failed {
+        if keyboard_input.just_pressed(KeyCode::KeyR) {
+            *progress = LoadingProgress::default();
+            game_state.set(GameState::Loading);
+        }
+    }
+    else if keyboard_input.just_pressed(KeyCode::Enter) {
+        game_state.set(GameState::InGame);
+    }
+
+    if keyboard_input.just_pressed(KeyCode::KeyQ) {
+        game_state.set(GameState::QuitGame);
+    }
+}
+
+fn despawn_loading_failed_dlg(
+    mut commands: Commands,
+    query: Query<Entity, With<LoadingFailedDlg>>,
+) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn();
+    }
+}
@@ -4,20 +4,34 @@ mod app_globals;
 mod app_setup;
 mod asset_loader;
 mod asteroids;
+mod boss;
 mod camera;
 mod collision_detection;
+mod config;
 mod despawn;
+mod difficulty;
+mod director;
 mod game_over;
+mod hazards;
 mod health;
 mod hud;
+mod lifetime;
 mod lighting;
+mod loading_screen;
+mod modes;
 mod movement;
+mod ore;
+mod physics;
+mod pickups;
+mod players;
 mod saucer;
 mod schedule;
 mod score_text;
+mod shop;
 mod sound;
 mod spaceship;
 mod state;
+mod waves;
 #[cfg(feature = "debug")]
 mod debug;
 
@@ -25,19 +39,33 @@ use app_globals::AppGlobalsPlugin;
 use app_setup::AppSetupPlugin;
 use asset_loader::AssetLoaderPlugin;
 use asteroids::AsteroidPlugin;
+use boss::BossPlugin;
 use camera::CameraPlugin;
 use collision_detection::CollisionDetectionPlugin;
+use config::ConfigPlugin;
 use despawn::DespawnPlugin;
+use difficulty::DifficultyPlugin;
+use director::DirectorPlugin;
 use game_over::GameOverPlugin;
+use hazards::HazardPlugin;
 use hud::HudPlugin;
+use lifetime::LifetimePlugin;
 use lighting::LightingPlugin;
+use loading_screen::LoadingScreenPlugin;
+use modes::GameModePlugin;
 use movement::MovementPlugin;
+use ore::OrePlugin;
+use physics::PhysicsPlugin;
+use pickups::PickupPlugin;
+use players::PlayersPlugin;
 use saucer::SaucerPlugin;
 use schedule::SchedulePlugin;
 use score_text::ScorePlugin;
+use shop::ShopPlugin;
 use sound::SoundPlugin;
 use spaceship::SpaceshipPlugin;
 use state::StatePlugin;
+use waves::WavePlugin;
 #[cfg(feature = "debug")]
 use debug::DebugPlugin;
 
@@ -59,15 +87,31 @@ fn main() {
             LightingPlugin,
             SoundPlugin,
             ScorePlugin,
+            DirectorPlugin,
         ))
         .add_plugins((
             // max 15 plugins in a tuple, so we split it up.
             StatePlugin,
             GameOverPlugin,
             HudPlugin,
+            LoadingScreenPlugin,
+            PickupPlugin,
+            OrePlugin,
+            ShopPlugin,
+            LifetimePlugin,
+            PhysicsPlugin,
+            HazardPlugin,
+            BossPlugin,
+            WavePlugin,
+            DifficultyPlugin,
+            ConfigPlugin,
             #[cfg(feature = "debug")]
             DebugPlugin,
         ))
+        .add_plugins((
+            GameModePlugin,
+            PlayersPlugin,
+        ))
         .run();
     
 }
@@ -0,0 +1,23 @@
+use bevy::prelude::*;
+
+use crate::{
+    players::respawn_players,
+    schedule::InGameSet,
+};
+use super::{end_game_when_players_out, in_mode, GameMode};
+
+
+// The original game: waves of asteroids until the ships are destroyed.  The
+// waves themselves are the asteroid plugin's job.
+pub struct ClassicPlugin;
+
+impl Plugin for ClassicPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update,
+            end_game_when_players_out
+                .after(respawn_players)
+                .in_set(InGameSet::EntityUpdates)
+                .run_if(in_mode(GameMode::Classic)),
+        );
+    }
+}
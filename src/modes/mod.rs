@@ -0,0 +1,215 @@
+use bevy::prelude::*;
+
+use crate::{
+    asset_loader::SceneAssets,
+    players::{PlayerCount, Players},
+    schedule::InGameSet,
+    state::{GameSetup, GameState},
+};
+
+pub mod classic;
+pub mod survival;
+pub mod time_attack;
+pub mod zen;
+use classic::ClassicPlugin;
+use survival::SurvivalPlugin;
+use time_attack::{TimeAttackPlugin, TIME_ATTACK_SECS};
+use zen::{ZenPlugin, ZEN_FINISH_KEY_NAME};
+
+const CLOCK_TEXT_COLOR: Color = Color::srgb(0.9, 0.9, 0.9);
+
+
+// Each mode has its own rules plugin, which decides what gets spawned and
+// how the game ends.  Like the difficulty, it is picked on the game over
+// screen and kept for the next game.
+#[derive(Resource, Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GameMode {
+    #[default]
+    Classic,
+    Survival,
+    TimeAttack,
+    Zen,
+}
+
+impl GameMode {
+    pub const ALL: [GameMode; 4] = [
+        GameMode::Classic,
+        GameMode::Survival,
+        GameMode::TimeAttack,
+        GameMode::Zen,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            GameMode::Classic => "Classic",
+            GameMode::Survival => "Survival",
+            GameMode::TimeAttack => "Time Attack",
+            GameMode::Zen => "Zen",
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            GameMode::Classic => "Waves of asteroids until you die.",
+            GameMode::Survival => "A steady stream of asteroids that keeps getting faster.",
+            GameMode::TimeAttack => "Score as much as you can in 3 minutes.",
+            GameMode::Zen => "Waves of asteroids, but nothing can hurt you.",
+        }
+    }
+
+    // Survival brings its own asteroids instead.
+    pub fn has_waves(&self) -> bool {
+        *self != GameMode::Survival
+    }
+
+    // Wraps around, so the menu can just keep cycling.
+    pub fn next(&self) -> Self {
+        let index = Self::ALL.iter().position(|m| m == self).unwrap_or(0);
+        Self::ALL[(index + 1) % Self::ALL.len()]
+    }
+
+    pub fn prev(&self) -> Self {
+        let index = Self::ALL.iter().position(|m| m == self).unwrap_or(0);
+        Self::ALL[(index + Self::ALL.len() - 1) % Self::ALL.len()]
+    }
+}
+
+// How long the current game has been going, not counting time paused or in
+// the shop.
+#[derive(Resource, Debug, Default)]
+pub struct ModeClock {
+    pub elapsed: f32,
+}
+
+#[derive(Component, Debug)]
+pub struct ModeClockText;
+
+// Why the last game ended.  Whatever sends the game to GameOver sets this
+// first, so the things that look back on the game can tell a death from a
+// game that simply finished.
+#[derive(Resource, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum GameEndReason {
+    #[default]
+    PlayersOut,
+    TimeUp,  // Time Attack's clock ran out
+    Finished,  // the player called it a day in Zen
+}
+
+
+pub struct GameModePlugin;
+
+impl Plugin for GameModePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<GameMode>()
+        .init_resource::<ModeClock>()
+        .init_resource::<GameEndReason>()
+        .add_plugins((
+            ClassicPlugin,
+            SurvivalPlugin,
+            TimeAttackPlugin,
+            ZenPlugin,
+        ))
+        .add_systems(GameSetup, spawn_mode_clock)
+        .add_systems(OnEnter(GameState::StartGame), reset_mode_clock)
+        .add_systems(Update,
+            tick_mode_clock.in_set(InGameSet::EntityUpdates),
+        )
+        .add_systems(Update, update_mode_clock);
+    }
+}
+
+// Run condition for the rules that belong to a single mode.
+pub fn in_mode(mode: GameMode) -> impl FnMut(Res<GameMode>) -> bool + Clone {
+    move |current: Res<GameMode>| *current == mode
+}
+
+pub fn has_waves(mode: Res<GameMode>) -> bool {
+    mode.has_waves()
+}
+
+// The usual way for a game to end: every player has lost their last ship.
+// The modes that have it add it for themselves, after the players have had
+// a chance to respawn.
+pub fn end_game_when_players_out(
+    mut next_state: ResMut<NextState<GameState>>,
+    mut end_reason: ResMut<GameEndReason>,
+    players: Res<Players>,
+    player_count: Res<PlayerCount>,
+) {
+    if players.all_out(*player_count) {
+        *end_reason = GameEndReason::PlayersOut;
+        next_state.set(GameState::GameOver);
+    }
+}
+
+fn reset_mode_clock(
+    mut clock: ResMut<ModeClock>,
+) {
+    clock.elapsed = 0.0;
+}
+
+fn tick_mode_clock(
+    mut clock: ResMut<ModeClock>,
+    time: Res<Time>,
+) {
+    clock.elapsed += time.delta_secs();
+}
+
+fn spawn_mode_clock(
+    mut commands: Commands,
+    scene_assets: Res<SceneAssets>,
+) {
+    commands.spawn((
+        Name::new("mode_clock_text"),
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Percent(8.0),
+            left: Val::Percent(40.0),
+            width: Val::Percent(24.0),
+            display: Display::None,
+            justify_content: JustifyContent::Center,
+            ..default()
+        },
+        Text::new(""),
+        TextFont {
+            font: scene_assets.font("font"),
+            font_size: 22.0,
+            ..default()
+        },
+        TextColor(CLOCK_TEXT_COLOR),
+        ModeClockText,
+    ));
+}
+
+fn update_mode_clock(
+    mode: Res<GameMode>,
+    clock: Res<ModeClock>,
+    mut query: Query<(&mut Text, &mut Node), With<ModeClockText>>,
+) {
+    let Ok((mut text, mut node)) = query.single_mut() else {
+        return;
+    };
+
+    let label = match *mode {
+        GameMode::Classic => None,
+        GameMode::Survival => Some(format!("Survived: {}", format_clock(clock.elapsed))),
+        GameMode::TimeAttack => Some(format!(
+            "Time left: {}",
+            format_clock((TIME_ATTACK_SECS - clock.elapsed).max(0.0)),
+        )),
+        GameMode::Zen => Some(format!("Zen - {} to finish", ZEN_FINISH_KEY_NAME)),
+    };
+
+    match label {
+        Some(label) => {
+            node.display = Display::Flex;
+            text.0 = label;
+        }
+        None => node.display = Display::None,
+    }
+}
+
+fn format_clock(secs: f32) -> String {
+    let secs = secs.ceil() as u32;
+    format!("{}:{:02}", secs / 60, secs % 60)
+}
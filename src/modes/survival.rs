@@ -0,0 +1,121 @@
+use bevy::{ecs::system::SystemParam, prelude::*};
+use rand::Rng;
+
+use crate::{
+    app_globals::AppGlobals,
+    asteroids::{
+        levels::wave_definition,
+        spawn_random_asteroid,
+        variants::roll_variant,
+        Asteroid,
+        WaveSetup,
+    },
+    players::respawn_players,
+    schedule::InGameSet,
+    spaceship::Spaceship,
+    state::GameState,
+};
+use super::{end_game_when_players_out, in_mode, GameMode, ModeClock};
+
+// Asteroids per second.  It starts off gentle and keeps on climbing.
+const SURVIVAL_START_RATE: f32 = 0.25;
+const SURVIVAL_RATE_GROWTH: f32 = 0.005;  // per second survived
+const SURVIVAL_MAX_RATE: f32 = 2.0;
+
+// Every so often things step up a level, which brings in the nastier
+// asteroid variants and saucers that the later waves have.
+const SURVIVAL_STAGE_SECS: f32 = 45.0;
+
+// Don't let the screen fill up completely.
+const SURVIVAL_MAX_ASTEROIDS: usize = 40;
+const SURVIVAL_BIG_ASTEROID_CHANCE: f32 = 0.6;
+
+
+#[derive(Resource, Debug, Default)]
+pub struct SurvivalSpawner {
+    pub next_in: f32,  // seconds until the next asteroid
+}
+
+// How long the game has been going, and so how soon the next asteroid is
+// due.
+#[derive(SystemParam)]
+struct SurvivalPace<'w> {
+    spawner: ResMut<'w, SurvivalSpawner>,
+    clock: Res<'w, ModeClock>,
+}
+
+
+// No waves, just a steady trickle of asteroids that gets faster the longer
+// you last.
+pub struct SurvivalPlugin;
+
+impl Plugin for SurvivalPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SurvivalSpawner>()
+        .add_systems(OnEnter(GameState::StartGame), reset_survival)
+        .add_systems(Update, (
+                end_game_when_players_out.after(respawn_players),
+                trickle_asteroids,
+            )
+            .in_set(InGameSet::EntityUpdates)
+            .run_if(in_mode(GameMode::Survival)),
+        );
+    }
+}
+
+fn reset_survival(
+    mut spawner: ResMut<SurvivalSpawner>,
+) {
+    *spawner = SurvivalSpawner::default();
+}
+
+fn trickle_asteroids(
+    mut commands: Commands,
+    spaceship_q: Query<&Transform, With<Spaceship>>,
+    asteroids: Query<(), With<Asteroid>>,
+    setup: WaveSetup,
+    pace: SurvivalPace,
+    mut app_globals: ResMut<AppGlobals>,
+    time: Res<Time>,
+) {
+    let SurvivalPace { mut spawner, clock } = pace;
+
+    // The level is what the next wave would be, in the other modes.
+    let stage = 1 + (clock.elapsed / SURVIVAL_STAGE_SECS) as i32;
+    if app_globals.level != stage + 1 {
+        app_globals.level = stage + 1;
+
+        #[cfg(debug_assertions)]
+        info!("Survival stage {:}", stage);
+    }
+
+    spawner.next_in -= time.delta_secs();
+    if spawner.next_in > 0.0 {
+        return;
+    }
+
+    let rate = (SURVIVAL_START_RATE + SURVIVAL_RATE_GROWTH * clock.elapsed).min(SURVIVAL_MAX_RATE);
+    spawner.next_in = 1.0 / rate;
+
+    if asteroids.iter().count() >= SURVIVAL_MAX_ASTEROIDS {
+        return;
+    }
+
+    let mut rng = rand::rng();
+    let size = if rng.random::<f32>() < SURVIVAL_BIG_ASTEROID_CHANCE { 2 } else { 1 };
+    // A boss wave has no asteroids of its own to go by.
+    let mut wave = wave_definition(stage);
+    if wave.boss {
+        wave = wave_definition(stage - 1);
+    }
+
+    let ship_positions = spaceship_q.iter().map(|xform| xform.translation).collect::<Vec<_>>();
+
+    spawn_random_asteroid(
+        &mut commands,
+        &ship_positions,
+        &setup,
+        size,
+        roll_variant(&wave.variant_weights, &mut rng),
+    );
+}
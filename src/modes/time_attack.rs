@@ -0,0 +1,41 @@
+use bevy::prelude::*;
+
+use crate::{
+    players::respawn_players,
+    schedule::InGameSet,
+    state::GameState,
+};
+use super::{end_game_when_players_out, in_mode, GameEndReason, GameMode, ModeClock};
+
+pub const TIME_ATTACK_SECS: f32 = 180.0;
+
+
+// Classic waves, but against the clock: score as much as you can before
+// time runs out.  Getting killed still ends it early.
+pub struct TimeAttackPlugin;
+
+impl Plugin for TimeAttackPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, (
+                end_game_when_players_out.after(respawn_players),
+                end_game_when_time_is_up,
+            )
+            .in_set(InGameSet::EntityUpdates)
+            .run_if(in_mode(GameMode::TimeAttack)),
+        );
+    }
+}
+
+fn end_game_when_time_is_up(
+    clock: Res<ModeClock>,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut end_reason: ResMut<GameEndReason>,
+) {
+    if clock.elapsed >= TIME_ATTACK_SECS {
+        #[cfg(debug_assertions)]
+        info!("Time attack is over");
+
+        *end_reason = GameEndReason::TimeUp;
+        next_state.set(GameState::GameOver);
+    }
+}
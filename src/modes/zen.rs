@@ -0,0 +1,54 @@
+use bevy::prelude::*;
+
+use crate::{
+    health::Health,
+    schedule::InGameSet,
+    spaceship::{upgrades::ShipStats, Spaceship},
+    state::GameState,
+};
+use super::{in_mode, GameEndReason, GameMode};
+
+const ZEN_FINISH_KEY: KeyCode = KeyCode::Backspace;
+pub const ZEN_FINISH_KEY_NAME: &str = "<Backspace>";
+
+
+// Nothing can hurt you, so the game only ends when you say so.
+pub struct ZenPlugin;
+
+impl Plugin for ZenPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update,
+            finish_zen_game
+                .in_set(InGameSet::UserInput)
+                .run_if(in_mode(GameMode::Zen)),
+        )
+        // All of the damage for this frame has been done by now, and dead
+        // things aren't cleaned up until the start of the next one.
+        .add_systems(Update,
+            repair_ship
+                .in_set(InGameSet::CollisionDetection)
+                .run_if(in_mode(GameMode::Zen)),
+        );
+    }
+}
+
+fn repair_ship(
+    mut query: Query<(&mut Health, &ShipStats), With<Spaceship>>,
+) {
+    for (mut health, stats) in query.iter_mut() {
+        if health.value < stats.max_health {
+            health.value = stats.max_health;
+        }
+    }
+}
+
+fn finish_zen_game(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut end_reason: ResMut<GameEndReason>,
+) {
+    if keyboard_input.just_pressed(ZEN_FINISH_KEY) {
+        *end_reason = GameEndReason::Finished;
+        next_state.set(GameState::GameOver);
+    }
+}
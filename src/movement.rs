@@ -7,9 +7,11 @@ use bevy::{
 use crate::{
     asteroids::Asteroid,
     collision_detection::Collider,
+    ore::OreChunk,
+    pickups::Pickup,
     saucer::Saucer,
     schedule::InGameSet,
-    spaceship::Spaceship,
+    spaceship::{projectiles::WrapAround, Spaceship},
 };
 
 
@@ -60,6 +62,12 @@ impl Rotation {
 }
 
 
+// Entities with this marker are allowed to fly off the edge of the screen
+// instead of wrapping around to the other side.
+#[derive(Component, Debug)]
+pub struct NoWrap;
+
+
 #[derive(Bundle)]
 pub struct SceneBundle {
     pub scene: SceneRoot,
@@ -86,6 +94,9 @@ impl Plugin for MovementPlugin {
                 wrap_position::<Asteroid>,
                 wrap_position::<Spaceship>,
                 wrap_position::<Saucer>,
+                wrap_position::<Pickup>,
+                wrap_position::<OreChunk>,
+                wrap_position::<WrapAround>,
             )
             .chain()
             .in_set(InGameSet::EntityUpdates),
@@ -105,27 +116,51 @@ fn update_position(mut query: Query<(&Velocity, &mut Transform)>, time: Res<Time
     }
 }
 
+// The edges of the play area, as (min, max) corners in the XZ plane.
+// Anything outside of this is off-screen.
+pub fn play_area_bounds(projection: &Projection) -> (Vec2, Vec2) {
+    let mut min = Vec2::ZERO;
+    let mut max = Vec2::ZERO;
+
+    let bounds = projection.get_frustum_corners(0.0, 80.0);
+    for b in bounds {
+        if min.x > b.x {min.x = b.x}
+        if max.x < b.x {max.x = b.x}
+        if min.y > b.y {min.y = b.y}
+        if max.y < b.y {max.y = b.y}
+    }
+
+    (min, max)
+}
+
+// A random spot in the play area, keeping `margin` away from the edges.  On
+// a very small window the margin shrinks to fit, and if there is no room at
+// all we just use the middle.
+pub fn random_play_area_position(rng: &mut impl Rng, min: Vec2, max: Vec2, margin: f32) -> Vec3 {
+    let margin = Vec2::splat(margin).min((max - min) * 0.25);
+    let (min, max) = (min + margin, max - margin);
+
+    if min.x >= max.x || min.y >= max.y {
+        let middle = (min + max) * 0.5;
+        return Vec3::new(middle.x, 0.0, middle.y);
+    }
+
+    Vec3::new(
+        rng.random_range(min.x..max.x),
+        0.0,
+        rng.random_range(min.y..max.y),
+    )
+}
+
 fn wrap_position<T: Component>(
     camera_query: Query<&Projection, With<Camera>>,
-    mut query: Query<&mut Transform, With<T>>,
+    mut query: Query<&mut Transform, (With<T>, Without<NoWrap>)>,
 ) {
     // Wrap the positions of the objects so that they don't just go off
     // into infinity, but wrap to the other side of the screen.
-
-    // When a missile goes off-screen, we despawn it.
-    let mut min_x: f32 = 0.0;
-    let mut max_x: f32 = 0.0;
-    let mut min_z: f32 = 0.0;
-    let mut max_z: f32 = 0.0;
-
     let projection = camera_query.single().unwrap();
-    let bounds = projection.get_frustum_corners(0.0, 80.0);
-    for b in bounds {
-        if min_x > b.x {min_x = b.x}
-        if max_x < b.x {max_x = b.x}
-        if min_z > b.y {min_z = b.y}
-        if max_z < b.y {max_z = b.y}
-    }
+    let (min, max) = play_area_bounds(projection);
+    let (min_x, max_x, min_z, max_z) = (min.x, max.x, min.y, max.y);
     
     for mut transform in query.iter_mut() {
         if transform.translation.x < min_x {
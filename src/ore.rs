@@ -0,0 +1,322 @@
+use std::{fs, ops::RangeInclusive, path::PathBuf};
+
+use bevy::{asset::io::file::FileAssetReader, prelude::*};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    asteroids::AsteroidSpawnChildrenEvent,
+    lifetime::Lifetime,
+    movement::{Acceleration, Velocity},
+    schedule::InGameSet,
+    spaceship::{nearest_ship, Spaceship, SPACESHIP_RADIUS},
+    state::GameState,
+};
+
+// The wallet is kept in this file, in the same folder as the assets folder
+// (like the config), so it is found wherever the game is started from.
+const ORE_WALLET_FILE: &str = "ore_wallet.ron";
+
+const ORE_CHUNK_SIZE: f32 = 0.35;
+const ORE_LIFETIME_SECS: f32 = 15.0;
+const ORE_SCATTER_SPEED: f32 = 4.0;
+const ORE_VELOCITY_SCALAR: f32 = 0.5;  // fraction of the asteroid's velocity
+const ORE_DRAG: f32 = 0.6;  // chunks slowly come to a stop on their own
+
+// The ship has a tractor beam that pulls in any ore within this radius.
+const TRACTOR_RADIUS: f32 = 12.0;
+const TRACTOR_STRENGTH: f32 = 60.0;
+const ORE_COLLECT_RADIUS: f32 = SPACESHIP_RADIUS;
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OreKind {
+    Iron,
+    Gold,
+    Meaty,
+}
+
+impl OreKind {
+    pub const ALL: [OreKind; 3] = [OreKind::Iron, OreKind::Gold, OreKind::Meaty];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            OreKind::Iron => "Iron",
+            OreKind::Gold => "Gold",
+            OreKind::Meaty => "Meaty",
+        }
+    }
+
+    // How many credits one chunk is worth.
+    pub fn value(&self) -> i32 {
+        match self {
+            OreKind::Iron => 1,
+            OreKind::Gold => 5,
+            OreKind::Meaty => 20,
+        }
+    }
+
+    fn index(&self) -> usize {
+        match self {
+            OreKind::Iron => 0,
+            OreKind::Gold => 1,
+            OreKind::Meaty => 2,
+        }
+    }
+
+    fn color(&self) -> Color {
+        match self {
+            OreKind::Iron => Color::srgb(0.6, 0.6, 0.65),
+            OreKind::Gold => Color::srgb(1.0, 0.8, 0.1),
+            OreKind::Meaty => Color::srgb(0.9, 0.15, 0.2),
+        }
+    }
+}
+
+// How much ore an asteroid of a given size breaks into.
+pub struct OreYield {
+    pub chunks: RangeInclusive<u32>,
+    pub weights: [(OreKind, u32); 3],
+}
+
+// Indexed by asteroid level (small, medium, big).  The bigger the rock, the
+// more ore, and the better the odds that it really does contain meaty ore.
+pub const ORE_YIELDS: [OreYield; 3] = [
+    OreYield {
+        chunks: 0..=1,
+        weights: [(OreKind::Iron, 90), (OreKind::Gold, 9), (OreKind::Meaty, 1)],
+    },
+    OreYield {
+        chunks: 1..=2,
+        weights: [(OreKind::Iron, 80), (OreKind::Gold, 17), (OreKind::Meaty, 3)],
+    },
+    OreYield {
+        chunks: 2..=4,
+        weights: [(OreKind::Iron, 70), (OreKind::Gold, 24), (OreKind::Meaty, 6)],
+    },
+];
+
+impl OreYield {
+    fn roll_kind(&self, rng: &mut impl Rng) -> OreKind {
+        let total: u32 = self.weights.iter().map(|(_, w)| w).sum();
+        let mut pick = rng.random_range(0..total);
+
+        for (kind, weight) in self.weights.iter() {
+            if pick < *weight {
+                return *kind;
+            }
+            pick -= weight;
+        }
+
+        OreKind::Iron
+    }
+}
+
+
+#[derive(Component, Debug)]
+pub struct OreChunk {
+    pub kind: OreKind,
+}
+
+// The player's ore credits.  This carries over from one game to the next,
+// and is saved so it is still there next time the game is started.
+#[derive(Resource, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OreWallet {
+    pub credits: i32,
+}
+
+impl OreWallet {
+    fn path() -> PathBuf {
+        FileAssetReader::get_base_path().join(ORE_WALLET_FILE)
+    }
+
+    fn load() -> Self {
+        let path = Self::path();
+
+        match fs::read_to_string(&path) {
+            Ok(text) => ron::from_str(&text).unwrap_or_else(|e| {
+                warn!("{}: {}, starting with an empty wallet", path.display(), e);
+                OreWallet::default()
+            }),
+            // The first time around there is nothing saved yet.
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => OreWallet::default(),
+            Err(e) => {
+                warn!("{}: {}, starting with an empty wallet", path.display(), e);
+                OreWallet::default()
+            }
+        }
+    }
+
+    fn save(&self) {
+        let path = Self::path();
+        let result = ron::to_string(self)
+            .map_err(|e| e.to_string())
+            .and_then(|text| fs::write(&path, text).map_err(|e| e.to_string()));
+
+        if let Err(e) = result {
+            warn!("Couldn't save the ore wallet to {}: {}", path.display(), e);
+        }
+    }
+}
+
+// What was collected during the current game, for the Game Over screen.
+#[derive(Resource, Debug, Default)]
+pub struct RunOreStats {
+    pub collected: [u32; 3],
+    pub credits: i32,
+}
+
+impl RunOreStats {
+    pub fn collected(&self, kind: OreKind) -> u32 {
+        self.collected[kind.index()]
+    }
+}
+
+#[derive(Resource, Debug)]
+pub struct OreAssets {
+    mesh: Handle<Mesh>,
+    materials: [Handle<StandardMaterial>; 3],
+}
+
+
+pub struct OrePlugin;
+
+impl Plugin for OrePlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(OreWallet::load())
+        .init_resource::<RunOreStats>()
+        .add_systems(Startup, create_ore_assets)
+        .add_systems(Update, (
+                spawn_ore_from_asteroids,
+                tractor_beam,
+                collect_ore,
+            )
+            .chain()
+            .in_set(InGameSet::EntityUpdates),
+        )
+        // The chunks themselves are cleaned up by the LifetimePlugin.
+        // The wallet is saved whenever it has changed for good: at the end of
+        // a game, and after shopping.
+        .add_systems(OnEnter(GameState::GameOver), save_ore_wallet)
+        .add_systems(OnExit(GameState::Shop), save_ore_wallet)
+        .add_systems(OnEnter(GameState::StartGame), reset_run_ore_stats);
+    }
+}
+
+fn create_ore_assets(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    let material = |kind: OreKind| {
+        let color = kind.color();
+        materials.add(StandardMaterial {
+            base_color: color,
+            emissive: color.to_linear() * 2.0,
+            ..default()
+        })
+    };
+
+    commands.insert_resource(OreAssets {
+        mesh: meshes.add(Sphere::new(ORE_CHUNK_SIZE)),
+        materials: OreKind::ALL.map(material),
+    });
+}
+
+fn spawn_ore_from_asteroids(
+    mut commands: Commands,
+    mut event_reader: MessageReader<AsteroidSpawnChildrenEvent>,
+    ore_assets: Res<OreAssets>,
+) {
+    // Every destroyed asteroid sends one of these, even the small ones that
+    // have no children to spawn.
+    let mut rng = rand::rng();
+
+    for event in event_reader.read() {
+        let ore_yield = &ORE_YIELDS[event.level.min(ORE_YIELDS.len() - 1)];
+        let chunks = rng.random_range(ore_yield.chunks.clone()) * event.variant.props().ore_scalar;
+
+        for _ in 0..chunks {
+            let kind = ore_yield.roll_kind(&mut rng);
+
+            let scatter = Quat::from_rotation_y(rng.random_range(0.0..std::f32::consts::TAU))
+                * Vec3::Z
+                * ORE_SCATTER_SPEED;
+
+            commands.spawn((
+                Name::new("ore"),
+                OreChunk { kind },
+                Lifetime::new(ORE_LIFETIME_SECS),
+                Velocity::new(event.velocity.value * ORE_VELOCITY_SCALAR + scatter),
+                Acceleration::new(Vec3::ZERO),
+                Mesh3d(ore_assets.mesh.clone()),
+                MeshMaterial3d(ore_assets.materials[kind.index()].clone()),
+                Transform::from_translation(event.xform.translation),
+            ));
+        }
+    }
+}
+
+fn tractor_beam(
+    ship_q: Query<&Transform, With<Spaceship>>,
+    mut ore_q: Query<(&Transform, &Velocity, &mut Acceleration), With<OreChunk>>,
+) {
+    for (xform, velocity, mut acceleration) in ore_q.iter_mut() {
+        // Left to itself, a chunk slowly drifts to a stop.
+        acceleration.value = -velocity.value * ORE_DRAG;
+
+        // In co-op, whichever ship is closest gets it.  The wallet is shared
+        // anyway.
+        let Some((ship_xform, _)) = nearest_ship(xform.translation, ship_q.iter().map(|xform| (xform, ()))) else {
+            continue;
+        };
+        let ship_position = ship_xform.translation;
+
+        let to_ship = ship_position - xform.translation;
+        let distance = to_ship.length();
+
+        if distance < TRACTOR_RADIUS {
+            // Pull harder the closer it gets, and kill the sideways drift so
+            // that it doesn't just orbit the ship.
+            let pull = 1.0 - distance / TRACTOR_RADIUS;
+            acceleration.value = to_ship.normalize_or_zero() * TRACTOR_STRENGTH * (0.3 + pull)
+                - velocity.value * 2.0;
+        }
+    }
+}
+
+fn collect_ore(
+    mut commands: Commands,
+    ship_q: Query<&Transform, With<Spaceship>>,
+    ore_q: Query<(Entity, &OreChunk, &Transform)>,
+    mut wallet: ResMut<OreWallet>,
+    mut run_stats: ResMut<RunOreStats>,
+) {
+    for (entity, ore, xform) in ore_q.iter() {
+        let in_reach = ship_q.iter().any(|ship_xform| {
+            xform.translation.distance(ship_xform.translation) <= ORE_COLLECT_RADIUS
+        });
+        if !in_reach {
+            continue;
+        }
+
+        commands.entity(entity).despawn();
+
+        wallet.credits += ore.kind.value();
+        run_stats.credits += ore.kind.value();
+        run_stats.collected[ore.kind.index()] += 1;
+    }
+}
+
+fn save_ore_wallet(
+    wallet: Res<OreWallet>,
+) {
+    wallet.save();
+}
+
+fn reset_run_ore_stats(
+    mut run_stats: ResMut<RunOreStats>,
+) {
+    *run_stats = RunOreStats::default();
+}
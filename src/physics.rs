@@ -0,0 +1,149 @@
+use bevy::prelude::*;
+
+use crate::{
+    asteroids::Asteroid,
+    collision_detection::{handle_collision_event, Collider},
+    movement::Velocity,
+    schedule::InGameSet,
+    spaceship::{projectiles::Piercing, SpaceshipMissile},
+};
+
+const MOMENTUM_TOGGLE_KEY: KeyCode = KeyCode::KeyM;
+
+// How bouncy asteroids are when they run into each other.  1.0 is
+// perfectly elastic.
+const ASTEROID_RESTITUTION: f32 = 1.0;
+
+// Missiles are tiny, but they are fast, so they still give a big rock a
+// bit of a nudge.
+pub const MISSILE_MASS: f32 = 0.25;
+
+
+// Without momentum, everything just passes through everything else and the
+// only thing a collision does is damage.  With it, asteroids bounce off each
+// other and missiles knock them around.  It is off to begin with, so the
+// game plays like the classic, until M is pressed.
+#[derive(Resource, Debug, Default)]
+pub struct PhysicsSettings {
+    pub momentum: bool,
+}
+
+#[derive(Component, Debug, Clone, Copy)]
+pub struct Mass {
+    pub value: f32,
+}
+
+impl Mass {
+    pub fn new(value: f32) -> Self {
+        Self { value }
+    }
+}
+
+
+pub struct PhysicsPlugin;
+
+impl Plugin for PhysicsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PhysicsSettings>()
+        .add_systems(Update,
+            toggle_momentum.in_set(InGameSet::UserInput),
+        )
+        .add_systems(Update, (
+                bounce_asteroids,
+                // Piercing missiles spend a few frames inside their target,
+                // so this has to see the target before it is registered as
+                // hit.
+                transfer_missile_momentum.before(handle_collision_event),
+            )
+            .in_set(InGameSet::EntityUpdates)
+            .run_if(momentum_enabled),
+        );
+    }
+}
+
+fn momentum_enabled(settings: Res<PhysicsSettings>) -> bool {
+    settings.momentum
+}
+
+fn toggle_momentum(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut settings: ResMut<PhysicsSettings>,
+) {
+    if keyboard_input.just_pressed(MOMENTUM_TOGGLE_KEY) {
+        settings.momentum = !settings.momentum;
+
+        #[cfg(debug_assertions)]
+        info!("Momentum physics: {}", settings.momentum);
+    }
+}
+
+fn bounce_asteroids(
+    mut query: Query<(Entity, &mut Transform, &mut Velocity, &Collider, &Mass), With<Asteroid>>,
+) {
+    // Both asteroids see the collision, so only handle each pair once.
+    let mut pairs = vec![];
+    for (entity, _, _, collider, _) in query.iter() {
+        for &other in collider.colliding_entities.iter() {
+            if entity < other && query.contains(other) {
+                pairs.push((entity, other));
+            }
+        }
+    }
+
+    for (a, b) in pairs {
+        let Ok([
+            (_, mut xform_a, mut velocity_a, collider_a, mass_a),
+            (_, mut xform_b, mut velocity_b, collider_b, mass_b),
+        ]) = query.get_many_mut([a, b]) else {
+            continue;
+        };
+
+        let normal = (xform_b.translation - xform_a.translation)
+            .with_y(0.0)
+            .normalize_or(Vec3::X);
+        let total_mass = mass_a.value + mass_b.value;
+
+        // Push them apart so they aren't overlapping any more, the lighter
+        // one moving further.
+        let overlap = collider_a.radius + collider_b.radius -
+                      xform_a.translation.distance(xform_b.translation);
+        if overlap > 0.0 {
+            xform_a.translation -= normal * overlap * (mass_b.value / total_mass);
+            xform_b.translation += normal * overlap * (mass_a.value / total_mass);
+        }
+
+        // Only bounce if they are heading towards each other, or they could
+        // get stuck together.
+        let closing_speed = (velocity_a.value - velocity_b.value).dot(normal);
+        if closing_speed <= 0.0 {
+            continue;
+        }
+
+        let impulse = (1.0 + ASTEROID_RESTITUTION) * closing_speed / total_mass;
+        velocity_a.value -= normal * impulse * mass_b.value;
+        velocity_b.value += normal * impulse * mass_a.value;
+    }
+}
+
+// Both queries get at the Velocity, so they have to be kept apart.
+type AsteroidNotMissile = (With<Asteroid>, Without<SpaceshipMissile>);
+
+fn transfer_missile_momentum(
+    missile_q: Query<(&Velocity, &Collider, &Mass, Option<&Piercing>), With<SpaceshipMissile>>,
+    mut asteroid_q: Query<(&mut Velocity, &Mass), AsteroidNotMissile>,
+) {
+    for (missile_velocity, collider, missile_mass, piercing) in missile_q.iter() {
+        for &entity in collider.colliding_entities.iter() {
+            // A piercing missile only gets one push on each rock.
+            if piercing.is_some_and(|p| p.hit.contains(&entity)) {
+                continue;
+            }
+
+            let Ok((mut velocity, mass)) = asteroid_q.get_mut(entity) else {
+                continue;
+            };
+
+            velocity.value += missile_velocity.value * (missile_mass.value / mass.value);
+        }
+    }
+}
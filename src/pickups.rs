@@ -0,0 +1,356 @@
+use bevy::prelude::*;
+use rand::Rng;
+
+use crate::{
+    asteroids::AsteroidSpawnChildrenEvent,
+    collision_detection::Collider,
+    health::Health,
+    lifetime::Lifetime,
+    movement::{Acceleration, Velocity},
+    schedule::InGameSet,
+    spaceship::{
+        shield::{Shield, ShieldReadyEvent},
+        upgrades::ShipStats,
+        ShieldController,
+        ShieldState,
+        Spaceship,
+    },
+};
+
+const PICKUP_RADIUS: f32 = 1.2;
+const PICKUP_LIFETIME_SECS: f32 = 12.0;
+const PICKUP_BLINK_SECS: f32 = 3.0;  // blink for this long before expiring
+const PICKUP_SPIN_SPEED: f32 = 2.0;
+const PICKUP_VELOCITY_SCALAR: f32 = 0.6;  // fraction of the asteroid's velocity
+
+const REPAIR_AMOUNT: f32 = 35.0;
+const RAPID_FIRE_SECS: f32 = 10.0;
+const SPREAD_SHOT_SECS: f32 = 10.0;
+const OVERCHARGE_SECS: f32 = 8.0;
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PickupKind {
+    ShieldRecharge,
+    RapidFire,
+    SpreadShot,
+    Repair,
+    Overcharge,
+}
+
+impl PickupKind {
+    fn color(&self) -> Color {
+        match self {
+            PickupKind::ShieldRecharge => Color::srgb(0.3, 0.8, 1.0),
+            PickupKind::RapidFire => Color::srgb(1.0, 0.8, 0.2),
+            PickupKind::SpreadShot => Color::srgb(1.0, 0.3, 0.9),
+            PickupKind::Repair => Color::srgb(0.2, 1.0, 0.2),
+            PickupKind::Overcharge => Color::srgb(1.0, 0.95, 0.6),
+        }
+    }
+}
+
+// What a destroyed asteroid of a given size might leave behind.
+// `chance` is the odds of dropping anything at all, and `weights` decides
+// which pickup it is when it does.
+pub struct PickupDropTable {
+    pub chance: f64,
+    pub weights: [(PickupKind, u32); 5],
+}
+
+// Indexed by asteroid level (small, medium, big).  Bigger rocks are more
+// work to break up, so they are more generous.
+pub const PICKUP_DROP_TABLES: [PickupDropTable; 3] = [
+    PickupDropTable {
+        chance: 0.04,
+        weights: [
+            (PickupKind::ShieldRecharge, 2),
+            (PickupKind::RapidFire, 4),
+            (PickupKind::SpreadShot, 2),
+            (PickupKind::Repair, 2),
+            (PickupKind::Overcharge, 0),
+        ],
+    },
+    PickupDropTable {
+        chance: 0.10,
+        weights: [
+            (PickupKind::ShieldRecharge, 3),
+            (PickupKind::RapidFire, 3),
+            (PickupKind::SpreadShot, 3),
+            (PickupKind::Repair, 2),
+            (PickupKind::Overcharge, 1),
+        ],
+    },
+    PickupDropTable {
+        chance: 0.25,
+        weights: [
+            (PickupKind::ShieldRecharge, 3),
+            (PickupKind::RapidFire, 2),
+            (PickupKind::SpreadShot, 3),
+            (PickupKind::Repair, 3),
+            (PickupKind::Overcharge, 2),
+        ],
+    },
+];
+
+impl PickupDropTable {
+    pub fn roll(&self, rng: &mut impl Rng) -> Option<PickupKind> {
+        if !rng.random_bool(self.chance) {
+            return None;
+        }
+
+        let total: u32 = self.weights.iter().map(|(_, w)| w).sum();
+        let mut pick = rng.random_range(0..total);
+
+        for (kind, weight) in self.weights.iter() {
+            if pick < *weight {
+                return Some(*kind);
+            }
+            pick -= weight;
+        }
+
+        None
+    }
+}
+
+
+#[derive(Component, Debug)]
+pub struct Pickup {
+    pub kind: PickupKind,
+}
+
+// Timed power-ups.  These live on the spaceship and are removed again
+// once their timer runs out.
+#[derive(Component, Debug)]
+pub struct RapidFire {
+    pub timer: Timer,
+}
+
+#[derive(Component, Debug)]
+pub struct SpreadShot {
+    pub timer: Timer,
+}
+
+// Tops up the shield and makes it bounce saucer missiles back where they
+// came from, instead of soaking up the damage.
+#[derive(Component, Debug)]
+pub struct ShieldOvercharge {
+    pub timer: Timer,
+}
+
+#[derive(Resource, Debug)]
+pub struct PickupAssets {
+    mesh: Handle<Mesh>,
+    materials: Vec<(PickupKind, Handle<StandardMaterial>)>,
+}
+
+impl PickupAssets {
+    fn material(&self, kind: PickupKind) -> Handle<StandardMaterial> {
+        self.materials
+            .iter()
+            .find(|(k, _)| *k == kind)
+            .map(|(_, h)| h.clone())
+            .unwrap_or_default()
+    }
+}
+
+
+pub struct PickupPlugin;
+
+impl Plugin for PickupPlugin {
+    // Pickups that are never collected are cleaned up by the LifetimePlugin.
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, create_pickup_assets)
+        .add_systems(Update, (
+                spawn_pickups_from_asteroids,
+                animate_pickups,
+                collect_pickups,
+                tick_timed_power_ups,
+            ).in_set(InGameSet::EntityUpdates),
+        );
+    }
+}
+
+fn create_pickup_assets(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    // There aren't any models for the pickups (yet), so they are just
+    // glowing little boxes, coloured by what they do.
+    let kinds = [
+        PickupKind::ShieldRecharge,
+        PickupKind::RapidFire,
+        PickupKind::SpreadShot,
+        PickupKind::Repair,
+        PickupKind::Overcharge,
+    ];
+
+    commands.insert_resource(PickupAssets {
+        mesh: meshes.add(Cuboid::from_length(PICKUP_RADIUS)),
+        materials: kinds
+            .iter()
+            .map(|kind| {
+                let color = kind.color();
+                (*kind, materials.add(StandardMaterial {
+                    base_color: color,
+                    emissive: color.to_linear() * 4.0,
+                    ..default()
+                }))
+            })
+            .collect(),
+    });
+}
+
+fn spawn_pickups_from_asteroids(
+    mut commands: Commands,
+    mut event_reader: MessageReader<AsteroidSpawnChildrenEvent>,
+    pickup_assets: Res<PickupAssets>,
+) {
+    // Every destroyed asteroid sends one of these (even the small ones that
+    // have no children), so it doubles as our "asteroid destroyed" event.
+    let mut rng = rand::rng();
+
+    for event in event_reader.read() {
+        let table = &PICKUP_DROP_TABLES[event.level.min(PICKUP_DROP_TABLES.len() - 1)];
+
+        let Some(kind) = table.roll(&mut rng) else {
+            continue;
+        };
+
+        commands.spawn((
+            Name::new("pickup"),
+            Pickup { kind },
+            Lifetime::new(PICKUP_LIFETIME_SECS),
+            Velocity::new(event.velocity.value * PICKUP_VELOCITY_SCALAR),
+            Acceleration::new(Vec3::ZERO),
+            Collider::new(PICKUP_RADIUS),
+            Mesh3d(pickup_assets.mesh.clone()),
+            MeshMaterial3d(pickup_assets.material(kind)),
+            Transform::from_translation(event.xform.translation),
+        ));
+
+        #[cfg(debug_assertions)]
+        info!("Dropped {:?} pickup", kind);
+    }
+}
+
+fn animate_pickups(
+    mut query: Query<(&Lifetime, &mut Transform, &mut Visibility), With<Pickup>>,
+    time: Res<Time>,
+) {
+    for (lifetime, mut xform, mut visibility) in query.iter_mut() {
+        xform.rotate_y(PICKUP_SPIN_SPEED * time.delta_secs());
+        xform.rotate_local_x(PICKUP_SPIN_SPEED * 0.5 * time.delta_secs());
+
+        // Blink when it is about to disappear.
+        let remaining = lifetime.timer.remaining_secs();
+        *visibility = if remaining < PICKUP_BLINK_SECS && (remaining * 8.0) as i32 % 2 == 0 {
+            Visibility::Hidden
+        }
+        else {
+            Visibility::Inherited
+        };
+    }
+}
+
+fn collect_pickups(
+    mut commands: Commands,
+    mut ship_q: Query<
+        (Entity, &Collider, &mut Health, &mut ShieldController, &ShipStats),
+        With<Spaceship>,
+    >,
+    mut shield_q: Query<(&Shield, &mut Health), Without<Spaceship>>,
+    pickup_q: Query<&Pickup>,
+    mut shield_ready_writer: MessageWriter<ShieldReadyEvent>,
+) {
+    // Both ships can touch a pickup in the same frame, but only one of them
+    // gets it.
+    let mut collected = vec![];
+
+    for (ship_e, collider, mut health, mut controller, stats) in ship_q.iter_mut() {
+        for &entity in collider.colliding_entities.iter() {
+            if collected.contains(&entity) {
+                continue;
+            }
+
+            let Ok(pickup) = pickup_q.get(entity) else {
+                continue;
+            };
+
+            commands.entity(entity).despawn();
+            collected.push(entity);
+
+            #[cfg(debug_assertions)]
+            info!("Collected {:?} pickup", pickup.kind);
+
+            let mut recharge_shield = || match controller.state {
+                ShieldState::Active => {
+                    // Top the current shield back up.
+                    for (shield, mut shield_health) in shield_q.iter_mut() {
+                        if shield.ship == ship_e {
+                            shield_health.value = shield.max_hp;
+                        }
+                    }
+                }
+                ShieldState::Recharging => {
+                    controller.charge = stats.shield_hp;
+                    controller.state = ShieldState::Ready;
+                    shield_ready_writer.write(ShieldReadyEvent { ship: ship_e });
+                }
+                ShieldState::Ready => {
+                    controller.charge = stats.shield_hp;
+                }
+            };
+
+            match pickup.kind {
+                PickupKind::ShieldRecharge => recharge_shield(),
+                PickupKind::Overcharge => {
+                    recharge_shield();
+                    commands.entity(ship_e).insert(ShieldOvercharge {
+                        timer: Timer::from_seconds(OVERCHARGE_SECS, TimerMode::Once),
+                    });
+                }
+                PickupKind::Repair => {
+                    health.value = (health.value + REPAIR_AMOUNT).min(stats.max_health);
+                }
+                PickupKind::RapidFire => {
+                    commands.entity(ship_e).insert(RapidFire {
+                        timer: Timer::from_seconds(RAPID_FIRE_SECS, TimerMode::Once),
+                    });
+                }
+                PickupKind::SpreadShot => {
+                    commands.entity(ship_e).insert(SpreadShot {
+                        timer: Timer::from_seconds(SPREAD_SHOT_SECS, TimerMode::Once),
+                    });
+                }
+            }
+        }
+    }
+}
+
+fn tick_timed_power_ups(
+    mut commands: Commands,
+    mut rapid_fire_q: Query<(Entity, &mut RapidFire)>,
+    mut spread_shot_q: Query<(Entity, &mut SpreadShot)>,
+    mut overcharge_q: Query<(Entity, &mut ShieldOvercharge)>,
+    time: Res<Time>,
+) {
+    for (entity, mut rapid_fire) in rapid_fire_q.iter_mut() {
+        if rapid_fire.timer.tick(time.delta()).just_finished() {
+            commands.entity(entity).remove::<RapidFire>();
+        }
+    }
+
+    for (entity, mut spread_shot) in spread_shot_q.iter_mut() {
+        if spread_shot.timer.tick(time.delta()).just_finished() {
+            commands.entity(entity).remove::<SpreadShot>();
+        }
+    }
+
+    for (entity, mut overcharge) in overcharge_q.iter_mut() {
+        if overcharge.timer.tick(time.delta()).just_finished() {
+            commands.entity(entity).remove::<ShieldOvercharge>();
+        }
+    }
+}
@@ -0,0 +1,276 @@
+use bevy::{ecs::system::SystemParam, prelude::*};
+
+use crate::{
+    app_globals::AppGlobals,
+    asset_loader::SceneAssets,
+    config::GameConfig,
+    schedule::InGameSet,
+    spaceship::{spawn_spaceship, upgrades::ShipUpgrades, Spaceship},
+    state::{GameSetup, GameState},
+};
+
+pub const MAX_PLAYERS: usize = 2;
+
+
+// Which player a ship belongs to.  Everything the ship fires carries it too,
+// so we know who to give the points to.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Player(pub usize);
+
+impl Player {
+    pub fn name(&self) -> String {
+        format!("P{}", self.0 + 1)
+    }
+}
+
+// The last player to damage something.  If it dies, they get the points.
+#[derive(Component, Debug, Clone, Copy)]
+pub struct LastHitBy(pub Player);
+
+// How many people are playing.  Like the mode and the difficulty, it is
+// picked on the game over screen.
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PlayerCount(pub usize);
+
+impl Default for PlayerCount {
+    fn default() -> Self {
+        Self(1)
+    }
+}
+
+impl PlayerCount {
+    pub fn is_coop(&self) -> bool {
+        self.0 > 1
+    }
+
+    pub fn players(&self) -> impl Iterator<Item = Player> {
+        (0..self.0).map(Player)
+    }
+
+    // Wraps around, like the other game over menus.
+    pub fn next(&self) -> Self {
+        Self(self.0 % MAX_PLAYERS + 1)
+    }
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct PlayerState {
+    pub score: i32,
+    pub lives: u32,  // counting the ship they are flying
+    pub respawn: Option<Timer>,  // waiting for their next ship
+    pub upgrades: ShipUpgrades,  // what their last ship had
+}
+
+// How each player is getting on.  The team score (the one the leaderboards
+// go by) is still in the AppGlobals.
+#[derive(Resource, Debug, Default)]
+pub struct Players {
+    pub states: [PlayerState; MAX_PLAYERS],
+}
+
+impl Players {
+    pub fn get(&self, player: Player) -> &PlayerState {
+        &self.states[player.0]
+    }
+
+    pub fn add_score(&mut self, player: Player, points: i32) {
+        self.states[player.0].score += points;
+    }
+
+    // Points the whole team earned, split as evenly as they will go so the
+    // players' scores still add up to the team's.
+    pub fn share_score(&mut self, player_count: PlayerCount, points: i32) {
+        let count = player_count.0 as i32;
+        for player in player_count.players() {
+            let share = points / count + i32::from((player.0 as i32) < points % count);
+            self.add_score(player, share);
+        }
+    }
+
+    // Everybody has lost their last ship.
+    pub fn all_out(&self, player_count: PlayerCount) -> bool {
+        player_count.players().all(|player| self.get(player).lives == 0)
+    }
+}
+
+// The team score, and each player's part in it.  Anything that hands out
+// points goes through here, so the players' scores always add up to the
+// team's.
+#[derive(SystemParam)]
+pub struct TeamScore<'w> {
+    app_globals: ResMut<'w, AppGlobals>,
+    players: ResMut<'w, Players>,
+    player_count: Res<'w, PlayerCount>,
+}
+
+impl TeamScore<'_> {
+    // Points one player earned for the team.
+    pub fn award(&mut self, player: Player, points: i32) {
+        self.app_globals.score += points;
+        self.players.add_score(player, points);
+    }
+
+    // Points the whole team earned, so everybody gets a share.
+    pub fn share(&mut self, points: i32) {
+        self.app_globals.score += points;
+        self.players.share_score(*self.player_count, points);
+    }
+}
+
+
+pub struct PlayersPlugin;
+
+impl Plugin for PlayersPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PlayerCount>()
+        .init_resource::<Players>()
+        .add_systems(GameSetup, reset_players)
+        .add_systems(OnEnter(GameState::StartGame), reset_players)
+        .add_systems(Update,
+            respawn_players.in_set(InGameSet::EntityUpdates),
+        );
+    }
+}
+
+fn reset_players(
+    mut players: ResMut<Players>,
+    player_count: Res<PlayerCount>,
+    config: Res<GameConfig>,
+) {
+    let lives = if player_count.is_coop() {
+        config.players.coop_lives
+    }
+    else {
+        config.players.solo_lives
+    };
+
+    *players = Players::default();
+    for player in player_count.players() {
+        players.states[player.0].lives = lives.max(1);
+    }
+}
+
+// A player who loses their ship gets another one after a short wait, as
+// long as they have lives left.  Upgrades are bought for the whole team, so
+// the new ship gets whatever a teammate has, or failing that, what their
+// old one had.
+pub fn respawn_players(
+    mut commands: Commands,
+    ship_q: Query<(&Player, Ref<ShipUpgrades>), With<Spaceship>>,
+    mut players: ResMut<Players>,
+    scene_assets: Res<SceneAssets>,
+    player_count: Res<PlayerCount>,
+    config: Res<GameConfig>,
+    time: Res<Time>,
+) {
+    for (player, upgrades) in ship_q.iter() {
+        if upgrades.is_changed() {
+            players.states[player.0].upgrades = ShipUpgrades::clone(&upgrades);
+        }
+    }
+
+    for player in player_count.players() {
+        if ship_q.iter().any(|(p, _)| *p == player) {
+            continue;
+        }
+
+        let state = &mut players.states[player.0];
+        if state.lives == 0 {
+            continue;
+        }
+
+        let Some(respawn) = state.respawn.as_mut() else {
+            // They have only just lost it.
+            state.lives -= 1;
+
+            #[cfg(debug_assertions)]
+            info!("{} lost a ship, {} left", player.name(), state.lives);
+
+            if state.lives > 0 {
+                state.respawn = Some(Timer::from_seconds(config.players.respawn_secs, TimerMode::Once));
+            }
+            continue;
+        };
+
+        if !respawn.tick(time.delta()).is_finished() {
+            continue;
+        }
+        state.respawn = None;
+
+        let upgrades = ship_q
+            .iter()
+            .map(|(_, upgrades)| ShipUpgrades::clone(&upgrades))
+            .next()
+            .unwrap_or_else(|| state.upgrades.clone());
+
+        spawn_spaceship(&mut commands, &scene_assets, &config, player, *player_count, upgrades);
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scores(players: &Players) -> [i32; MAX_PLAYERS] {
+        players.states.clone().map(|state| state.score)
+    }
+
+    #[test]
+    fn a_lone_player_gets_all_of_it() {
+        let mut players = Players::default();
+        players.share_score(PlayerCount(1), 101);
+
+        assert_eq!(scores(&players), [101, 0]);
+    }
+
+    #[test]
+    fn two_players_split_it_evenly() {
+        let mut players = Players::default();
+        players.share_score(PlayerCount(2), 100);
+
+        assert_eq!(scores(&players), [50, 50]);
+    }
+
+    #[test]
+    fn an_odd_total_still_adds_up() {
+        let mut players = Players::default();
+        players.share_score(PlayerCount(2), 101);
+        players.share_score(PlayerCount(2), 1);
+
+        assert_eq!(scores(&players).iter().sum::<i32>(), 102);
+        assert_eq!(scores(&players), [52, 50]);
+    }
+
+    #[test]
+    fn nothing_to_share_changes_nothing() {
+        let mut players = Players::default();
+        players.share_score(PlayerCount(2), 0);
+
+        assert_eq!(scores(&players), [0, 0]);
+    }
+
+    #[test]
+    fn player_count_cycles_through_every_count() {
+        let mut count = PlayerCount::default();
+        assert_eq!(count, PlayerCount(1));
+
+        for expected in (2..=MAX_PLAYERS).chain([1]) {
+            count = count.next();
+            assert_eq!(count, PlayerCount(expected));
+        }
+    }
+
+    #[test]
+    fn only_everybody_out_is_all_out() {
+        let mut players = Players::default();
+        players.states[0].lives = 0;
+        players.states[1].lives = 2;
+
+        assert!(players.all_out(PlayerCount(1)));
+        assert!(!players.all_out(PlayerCount(2)));
+
+        players.states[1].lives = 0;
+        assert!(players.all_out(PlayerCount(2)));
+    }
+}
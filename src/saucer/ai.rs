@@ -0,0 +1,265 @@
+use bevy::prelude::*;
+use rand::Rng;
+
+use crate::{
+    asteroids::Asteroid,
+    collision_detection::Collider,
+    movement::{Acceleration, NoWrap, Velocity},
+    schedule::InGameSet,
+    spaceship::{nearest_ship, Spaceship},
+};
+use super::Saucer;
+
+// How hard the saucer steers toward the velocity it wants.
+const SAUCER_STEERING_GAIN: f32 = 2.0;
+
+// Anything closer than this (on top of both colliders) gets steered away from.
+const SAUCER_AVOID_DISTANCE: f32 = 8.0;
+const SAUCER_AVOID_STRENGTH: f32 = 30.0;
+
+// Anything closer than this (on top of both colliders) is a real threat, and
+// dodging it takes priority over everything else.
+const SAUCER_DANGER_DISTANCE: f32 = 3.0;
+
+// How close to the player we stay when holding position.
+const SAUCER_DISTANCE_BAND: f32 = 4.0;
+const SAUCER_ORBIT_SPEED: f32 = 0.5;  // fraction of max speed
+
+// Every so often the saucer makes a run straight past the player.
+const SAUCER_STRAFE_INTERVAL_SECS: f32 = 6.0;
+const SAUCER_STRAFE_MAX_SECS: f32 = 3.0;
+const SAUCER_STRAFE_SIDE_OFFSET: f32 = 6.0;
+const SAUCER_STRAFE_ARRIVED_DISTANCE: f32 = 3.0;
+
+
+// The saucer's behaviours, in order of priority.  Every frame we walk down
+// this list and pick the first one whose conditions are met:
+//
+//   Leave   - we have been around long enough, fly off the screen.
+//   Evade   - something is about to hit us.
+//   Strafe  - make a run past the player.
+//   Hold    - keep our preferred firing distance and circle the player.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SaucerBehavior {
+    Leave,
+    Evade,
+    Strafe,
+    Hold,
+}
+
+#[derive(Component, Debug)]
+pub struct SaucerAi {
+    pub behavior: SaucerBehavior,
+    pub lifetime: Timer,
+    pub strafe_cooldown: Timer,
+    pub strafe_timer: Timer,
+    pub strafe_target: Vec3,
+    pub exit_direction: Vec3,
+    pub orbit_direction: f32,
+}
+
+impl SaucerAi {
+    pub fn new(lifetime_secs: f32) -> Self {
+        let mut strafe_timer = Timer::from_seconds(SAUCER_STRAFE_MAX_SECS, TimerMode::Once);
+        strafe_timer.set_elapsed(strafe_timer.duration()); // not strafing yet
+
+        Self {
+            behavior: SaucerBehavior::Hold,
+            lifetime: Timer::from_seconds(lifetime_secs, TimerMode::Once),
+            strafe_cooldown: Timer::from_seconds(SAUCER_STRAFE_INTERVAL_SECS, TimerMode::Once),
+            strafe_timer,
+            strafe_target: Vec3::ZERO,
+            exit_direction: Vec3::X,
+            orbit_direction: if rand::rng().random_bool(0.5) { 1.0 } else { -1.0 },
+        }
+    }
+}
+
+
+pub struct SaucerAiPlugin;
+
+impl Plugin for SaucerAiPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, (
+                saucer_choose_behavior,
+                saucer_steering,
+            )
+            .chain()
+            .in_set(InGameSet::EntityUpdates),
+        );
+    }
+}
+
+// Everything a saucer has to keep clear of.
+type Obstacles<'w, 's> = Query<
+    'w,
+    's,
+    (Entity, &'static Transform, &'static Collider),
+    Or<(With<Asteroid>, With<Saucer>)>,
+>;
+
+
+fn saucer_choose_behavior(
+    mut commands: Commands,
+    mut query: Query<(Entity, &Transform, &Collider, &mut SaucerAi), With<Saucer>>,
+    obstacles: Obstacles,
+    spaceship: Query<&Transform, With<Spaceship>>,
+    time: Res<Time>,
+) {
+    for (entity, saucer_xform, saucer_collider, mut ai) in query.iter_mut() {
+        ai.lifetime.tick(time.delta());
+        ai.strafe_cooldown.tick(time.delta());
+        ai.strafe_timer.tick(time.delta());
+
+        // Once we decide to leave, there is no going back.
+        if ai.behavior == SaucerBehavior::Leave {
+            continue;
+        }
+
+        if ai.lifetime.is_finished() {
+            // Head for whichever side of the screen is closest, and stop
+            // wrapping around so that we actually get to leave.
+            let position = saucer_xform.translation;
+            ai.exit_direction = if position.x.abs() > position.z.abs() {
+                Vec3::X * position.x.signum()
+            }
+            else {
+                Vec3::Z * position.z.signum()
+            };
+            ai.behavior = SaucerBehavior::Leave;
+            commands.entity(entity).insert(NoWrap);
+
+            #[cfg(debug_assertions)]
+            info!("Saucer {:?} is leaving", entity);
+            continue;
+        }
+
+        let in_danger = obstacles.iter().any(|(obstacle_entity, obstacle_xform, obstacle_collider)| {
+            obstacle_entity != entity &&
+            obstacle_xform.translation.distance(saucer_xform.translation) <
+                saucer_collider.radius + obstacle_collider.radius + SAUCER_DANGER_DISTANCE
+        });
+
+        if in_danger {
+            ai.behavior = SaucerBehavior::Evade;
+            continue;
+        }
+
+        if ai.behavior == SaucerBehavior::Strafe &&
+           !ai.strafe_timer.is_finished() &&
+           ai.strafe_target.distance(saucer_xform.translation) > SAUCER_STRAFE_ARRIVED_DISTANCE
+        {
+            continue;  // keep going
+        }
+
+        let Some((spaceship_xform, _)) = nearest_ship(
+            saucer_xform.translation,
+            spaceship.iter().map(|xform| (xform, ())),
+        ) else {
+            ai.behavior = SaucerBehavior::Hold;
+            continue;
+        };
+
+        if ai.strafe_cooldown.is_finished() {
+            // Pick a point on the far side of the player, a little off to the
+            // side so that we fly past them rather than into them.
+            let to_player = (spaceship_xform.translation - saucer_xform.translation)
+                .normalize_or_zero();
+            let side = to_player.cross(Vec3::Y) * ai.orbit_direction;
+
+            ai.strafe_target = spaceship_xform.translation
+                + to_player * SAUCER_STRAFE_SIDE_OFFSET * 2.0
+                + side * SAUCER_STRAFE_SIDE_OFFSET;
+            ai.strafe_timer.reset();
+            ai.strafe_cooldown.reset();
+            ai.behavior = SaucerBehavior::Strafe;
+            continue;
+        }
+
+        ai.behavior = SaucerBehavior::Hold;
+    }
+}
+
+
+fn saucer_steering(
+    mut query: Query<(Entity, &Saucer, &SaucerAi, &Transform, &Collider, &Velocity, &mut Acceleration)>,
+    obstacles: Obstacles,
+    spaceship: Query<&Transform, With<Spaceship>>,
+) {
+    for (
+        entity,
+        saucer,
+        ai,
+        saucer_xform,
+        saucer_collider,
+        saucer_vel,
+        mut saucer_accel,
+    ) in query.iter_mut() {
+        let props = saucer.kind.props();
+        let position = saucer_xform.translation;
+        let spaceship_position = nearest_ship(position, spaceship.iter().map(|xform| (xform, ())))
+            .map(|(xform, _)| xform.translation);
+
+        // Push away from everything nearby, not just the closest thing.
+        // The push is along the vector from the obstacle to us, and gets
+        // stronger the closer it is.
+        let mut avoidance = Vec3::ZERO;
+        for (obstacle_entity, obstacle_xform, obstacle_collider) in obstacles.iter() {
+            if obstacle_entity == entity {
+                continue;  // This is us
+            }
+
+            let away = position - obstacle_xform.translation;
+            let gap = away.length() - saucer_collider.radius - obstacle_collider.radius;
+
+            if gap < SAUCER_AVOID_DISTANCE {
+                let closeness = 1.0 - (gap.max(0.0) / SAUCER_AVOID_DISTANCE);
+                avoidance += away.normalize_or_zero() * closeness * SAUCER_AVOID_STRENGTH;
+            }
+        }
+
+        let desired_velocity = match ai.behavior {
+            SaucerBehavior::Leave => ai.exit_direction * props.max_speed,
+            SaucerBehavior::Evade => avoidance.normalize_or_zero() * props.max_speed,
+            SaucerBehavior::Strafe => {
+                (ai.strafe_target - position).normalize_or_zero() * props.max_speed
+            }
+            SaucerBehavior::Hold => match spaceship_position {
+                Some(target) => {
+                    let to_player = target - position;
+                    let distance = to_player.length();
+                    let to_player = to_player.normalize_or_zero();
+
+                    // Close in or back off until we are at a comfortable
+                    // firing distance, then circle around the player.
+                    let radial = if distance > props.preferred_distance + SAUCER_DISTANCE_BAND {
+                        to_player
+                    }
+                    else if distance < props.preferred_distance - SAUCER_DISTANCE_BAND {
+                        -to_player
+                    }
+                    else {
+                        Vec3::ZERO
+                    };
+                    let orbit = to_player.cross(Vec3::Y) * ai.orbit_direction * SAUCER_ORBIT_SPEED;
+
+                    (radial + orbit).normalize_or_zero() * props.max_speed * 0.6
+                }
+                // No player to bother, so just drift around the middle of the
+                // screen rather than hugging the edges.
+                None => (Vec3::ZERO - position).normalize_or_zero() * props.max_speed * 0.3,
+            },
+        };
+
+        saucer_accel.value = (desired_velocity - saucer_vel.value) * SAUCER_STEERING_GAIN;
+
+        if ai.behavior != SaucerBehavior::Leave {
+            saucer_accel.value += avoidance;
+        }
+
+        if saucer_vel.value.length() > props.max_speed {
+            // we are going too fast.  Put on the brakes
+            saucer_accel.value -= saucer_vel.value;
+        }
+    }
+}
@@ -0,0 +1,229 @@
+use std::f32::consts::FRAC_PI_2;
+
+use bevy::prelude::*;
+
+use crate::schedule::InGameSet;
+use super::Saucer;
+
+// How long a saucer telegraphs a shot before it actually fires.
+const SAUCER_TELEGRAPH_SECS: f32 = 0.45;
+const SAUCER_TELEGRAPH_LENGTH: f32 = 12.0;
+const SAUCER_TELEGRAPH_COLOR: Color = Color::srgba(1.0, 0.25, 0.2, 0.8);
+
+// The aim gets better as the player's score goes up.  This is how much
+// each point of score shrinks a saucer's inaccuracy cone (radians).
+const SAUCER_AIM_ERROR_PER_POINT: f32 = 0.002;
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SaucerAimMode {
+    // Fire in any direction at all.
+    Random,
+    // Fire at where the ship will be when the missile gets there.
+    Intercept,
+}
+
+// Added to a saucer when it decides to shoot.  While this is on, the saucer
+// shows the player where it is about to fire, and the shot goes off when the
+// timer runs out.
+#[derive(Component, Debug)]
+pub struct SaucerShotWindup {
+    pub timer: Timer,
+    pub direction: Vec3,
+}
+
+impl SaucerShotWindup {
+    pub fn new(direction: Vec3) -> Self {
+        Self {
+            timer: Timer::from_seconds(SAUCER_TELEGRAPH_SECS, TimerMode::Once),
+            direction,
+        }
+    }
+}
+
+
+pub struct SaucerAimPlugin;
+
+impl Plugin for SaucerAimPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update,
+            telegraph_saucer_shots.in_set(InGameSet::EntityUpdates),
+        );
+    }
+}
+
+
+// Work out how long it takes a projectile fired from `shooter` at `speed` to
+// meet a target at `target` that keeps moving at `target_velocity`.
+//
+// We need the time `t` where |target + target_velocity * t - shooter| equals
+// `speed * t`.  Squaring both sides gives us a quadratic in `t`:
+//
+//   (v.v - s^2) t^2 + 2 (p.v) t + p.p = 0
+//
+// where p is the target's position relative to the shooter.  Returns None
+// if the target is outrunning the projectile.
+pub fn intercept_time(
+    shooter: Vec3,
+    target: Vec3,
+    target_velocity: Vec3,
+    speed: f32,
+) -> Option<f32> {
+    let p = target - shooter;
+    let v = target_velocity;
+
+    let a = v.dot(v) - speed * speed;
+    let b = 2.0 * p.dot(v);
+    let c = p.dot(p);
+
+    if a.abs() < f32::EPSILON {
+        // The target moves exactly as fast as the projectile, so the
+        // equation is linear.
+        if b.abs() < f32::EPSILON {
+            return None;
+        }
+        let t = -c / b;
+        return (t > 0.0).then_some(t);
+    }
+
+    let discriminant = b * b - 4.0 * a * c;
+    if discriminant < 0.0 {
+        return None;
+    }
+
+    let root = discriminant.sqrt();
+    let t1 = (-b - root) / (2.0 * a);
+    let t2 = (-b + root) / (2.0 * a);
+
+    // We want the earliest time that is still in the future.
+    [t1, t2]
+        .into_iter()
+        .filter(|t| *t > 0.0)
+        .reduce(f32::min)
+}
+
+// The direction to fire in, before any inaccuracy is applied.
+pub fn aim_direction(
+    mode: SaucerAimMode,
+    shooter: Vec3,
+    target: Vec3,
+    target_velocity: Vec3,
+    speed: f32,
+    random_angle: f32,
+) -> Vec3 {
+    match mode {
+        SaucerAimMode::Random => Quat::from_rotation_y(random_angle) * Vec3::Z,
+        SaucerAimMode::Intercept => {
+            // If we can't catch the ship, fall back to shooting right at it.
+            let aim_point = intercept_time(shooter, target, target_velocity, speed)
+                .map(|t| target + target_velocity * t)
+                .unwrap_or(target);
+            (aim_point - shooter).normalize_or_zero()
+        }
+    }
+}
+
+// Half the width (radians) of the cone that a saucer's shots land in.
+pub fn inaccuracy_cone(max_error: f32, min_error: f32, score: i32) -> f32 {
+    (max_error - score.max(0) as f32 * SAUCER_AIM_ERROR_PER_POINT).max(min_error)
+}
+
+
+fn telegraph_saucer_shots(
+    mut gizmos: Gizmos,
+    query: Query<(&Transform, &SaucerShotWindup), With<Saucer>>,
+) {
+    for (xform, windup) in query.iter() {
+        // The line grows out toward the target as the shot charges up.
+        let t = windup.timer.fraction();
+        let start = xform.translation;
+        let end = start + windup.direction * SAUCER_TELEGRAPH_LENGTH * t;
+
+        gizmos.line(start, end, SAUCER_TELEGRAPH_COLOR);
+        gizmos.circle(
+            Isometry3d::new(start, Quat::from_rotation_x(FRAC_PI_2)),
+            1.0 + 2.0 * (1.0 - t),
+            SAUCER_TELEGRAPH_COLOR,
+        );
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SPEED: f32 = 40.0;
+
+    #[test]
+    fn a_still_target_is_hit_straight_on() {
+        let t = intercept_time(Vec3::ZERO, Vec3::new(0.0, 0.0, 20.0), Vec3::ZERO, SPEED).unwrap();
+        assert!((t - 0.5).abs() < 1e-4);
+
+        let direction = aim_direction(
+            SaucerAimMode::Intercept,
+            Vec3::ZERO,
+            Vec3::new(0.0, 0.0, 20.0),
+            Vec3::ZERO,
+            SPEED,
+            0.0,
+        );
+        assert!(direction.abs_diff_eq(Vec3::Z, 1e-4));
+    }
+
+    #[test]
+    fn a_crossing_target_is_led() {
+        let target = Vec3::new(0.0, 0.0, 20.0);
+        let velocity = Vec3::new(10.0, 0.0, 0.0);
+
+        let t = intercept_time(Vec3::ZERO, target, velocity, SPEED).unwrap();
+        let direction = aim_direction(SaucerAimMode::Intercept, Vec3::ZERO, target, velocity, SPEED, 0.0);
+
+        // The missile and the target end up in the same place.
+        let missile = direction * SPEED * t;
+        assert!(missile.abs_diff_eq(target + velocity * t, 1e-3));
+        assert!(direction.x > 0.0);
+    }
+
+    #[test]
+    fn a_target_running_away_faster_cant_be_caught() {
+        let target = Vec3::new(0.0, 0.0, 20.0);
+        let velocity = Vec3::new(0.0, 0.0, SPEED * 2.0);
+
+        assert_eq!(intercept_time(Vec3::ZERO, target, velocity, SPEED), None);
+
+        // So we just shoot at where it is now.
+        let direction = aim_direction(SaucerAimMode::Intercept, Vec3::ZERO, target, velocity, SPEED, 0.0);
+        assert!(direction.abs_diff_eq(Vec3::Z, 1e-4));
+    }
+
+    #[test]
+    fn a_target_as_fast_as_the_missile_going_away_cant_be_caught() {
+        let target = Vec3::new(0.0, 0.0, 20.0);
+        let velocity = Vec3::new(0.0, 0.0, SPEED);
+
+        assert_eq!(intercept_time(Vec3::ZERO, target, velocity, SPEED), None);
+    }
+
+    #[test]
+    fn random_mode_ignores_the_target() {
+        let direction = aim_direction(
+            SaucerAimMode::Random,
+            Vec3::ZERO,
+            Vec3::new(0.0, 0.0, 20.0),
+            Vec3::ZERO,
+            SPEED,
+            FRAC_PI_2,
+        );
+        assert!(direction.abs_diff_eq(Vec3::X, 1e-4));
+    }
+
+    #[test]
+    fn the_cone_shrinks_with_score_down_to_the_minimum() {
+        assert_eq!(inaccuracy_cone(0.35, 0.04, 0), 0.35);
+        assert!(inaccuracy_cone(0.35, 0.04, 50) < 0.35);
+        assert_eq!(inaccuracy_cone(0.35, 0.04, 1_000_000), 0.04);
+        // A negative score doesn't make it any worse.
+        assert_eq!(inaccuracy_cone(0.35, 0.04, -100), 0.35);
+    }
+}
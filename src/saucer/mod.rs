@@ -0,0 +1,427 @@
+use bevy::{
+    audio::Volume,
+    ecs::system::SystemParam,
+    prelude::*,
+};
+use rand::Rng;
+
+use crate::{
+    app_globals::AppGlobals,
+    sound::ambient::SaucerSound,
+    asset_loader::SceneAssets,
+    asteroids::levels::ASTEROID_SIZE_PROPS,
+    collision_detection::{Collider, CollisionDamage},
+    config::GameConfig,
+    difficulty::{Difficulty, DifficultySettings},
+    director::Director,
+    health::Health,
+    lifetime::MaxRange,
+    movement::{
+        Acceleration,
+        MovingObjectBundle,
+        Rotation,
+        SceneBundle,
+        Velocity,
+    },
+    schedule::InGameSet,
+    spaceship::{nearest_ship, Spaceship},
+    sound::effects::SaucerShootingSoundEvent,
+};
+
+pub mod ai;
+use ai::{SaucerAi, SaucerAiPlugin};
+
+pub mod aim;
+use aim::{
+    aim_direction,
+    inaccuracy_cone,
+    SaucerAimMode,
+    SaucerAimPlugin,
+    SaucerShotWindup,
+};
+
+
+const SAUCER_STARTING_VELOCITY: Vec3 = Vec3::new(1.0, 0.0, -1.0);
+
+// The chance of getting a small saucer instead of a large one goes up by
+// this much every level, starting at SMALL_SAUCER_FIRST_LEVEL.
+const SMALL_SAUCER_FIRST_LEVEL: i32 = 3;
+const SMALL_SAUCER_CHANCE_PER_LEVEL: f32 = 0.15;
+const SMALL_SAUCER_MAX_CHANCE: f32 = 0.8;
+
+const SAUCER_MISSILE_FORWARD_SPAWN_SCALAR: f32 = 4.0;
+const SAUCER_MISSILE_RADIUS: f32 = 0.5;
+const SAUCER_MISSILE_SIZE: f32 = 0.10;
+const SAUCER_MISSILE_RATE: f32 = 60.0;  // shots per second
+
+
+// In the spirit of the original arcade game, there are two saucers.
+// The large one is a slow, easy target that sprays missiles around at
+// random.  The small one is quick, hard to hit, and aims at the player.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SaucerKind {
+    Large,
+    Small,
+}
+
+pub struct SaucerProperties {
+    pub name: &'static str,
+    pub radius: f32,
+    pub size: f32,
+    pub health: f32,
+    pub max_speed: f32,
+    pub preferred_distance: f32,
+    pub lifetime_secs: f32,
+    pub points: i32,
+    pub aim_mode: SaucerAimMode,
+}
+
+const LARGE_SAUCER_PROPS: SaucerProperties = SaucerProperties {
+    name: "saucer_large",
+    radius: 2.5,
+    size: 0.7,
+    health: 100.0,
+    max_speed: 20.0,
+    preferred_distance: 22.0,
+    lifetime_secs: 25.0,
+    points: 20,
+    aim_mode: SaucerAimMode::Random,
+};
+
+const SMALL_SAUCER_PROPS: SaucerProperties = SaucerProperties {
+    name: "saucer_small",
+    radius: 1.4,
+    size: 0.4,
+    health: 30.0,
+    max_speed: 28.0,
+    preferred_distance: 14.0,
+    lifetime_secs: 18.0,
+    points: 100,
+    aim_mode: SaucerAimMode::Intercept,
+};
+
+impl SaucerKind {
+    pub fn props(&self) -> &'static SaucerProperties {
+        match self {
+            SaucerKind::Large => &LARGE_SAUCER_PROPS,
+            SaucerKind::Small => &SMALL_SAUCER_PROPS,
+        }
+    }
+
+    // Choose which saucer to send in, based on how far along we are.
+    pub fn for_level(level: i32) -> Self {
+        let small_chance = ((level - SMALL_SAUCER_FIRST_LEVEL + 1) as f32
+            * SMALL_SAUCER_CHANCE_PER_LEVEL)
+            .clamp(0.0, SMALL_SAUCER_MAX_CHANCE);
+
+        if rand::rng().random_bool(small_chance as f64) {
+            SaucerKind::Small
+        }
+        else {
+            SaucerKind::Large
+        }
+    }
+}
+
+#[derive(Component, Debug)]
+pub struct Saucer {
+    pub kind: SaucerKind,
+}
+
+#[derive(Component, Debug)]
+pub struct SaucerMissile;
+
+#[derive(Resource, Debug)]
+pub struct SaucerSpawnTimer {
+    pub timer: Timer,
+}
+
+#[derive(Resource, Debug)]
+pub struct SaucerMissileRateTimer {
+    timer: Timer,
+}
+
+
+#[derive(Message, Debug)]
+pub struct SaucerSpawnEvent;
+
+
+pub struct SaucerPlugin;
+
+impl Plugin for SaucerPlugin {
+    fn build(&self, app: &mut App) {
+        app
+        .insert_resource(SaucerSpawnTimer {
+            // The difficulty sets the real duration.
+            timer: Timer::from_seconds(
+                DifficultySettings::default().saucer_spawn_secs,
+                TimerMode::Repeating
+            )
+        })
+        .insert_resource(SaucerMissileRateTimer {
+            timer: Timer::from_seconds(
+                1.0 / SAUCER_MISSILE_RATE,
+                TimerMode::Repeating,
+            )
+        })
+        .add_plugins((
+            SaucerAiPlugin,
+            SaucerAimPlugin,
+        ))
+        .add_systems(Update, (
+                trigger_spawn_saucer,
+                handle_saucer_spawn_event,
+                saucer_sound_control,
+                saucer_weapon_control,
+                saucer_fire_after_windup,
+            ).in_set(InGameSet::EntityUpdates),
+        )
+        .add_message::<SaucerSpawnEvent>();
+    }
+}
+
+
+fn trigger_spawn_saucer(
+    mut spawn_timer: ResMut<SaucerSpawnTimer>,
+    mut saucer_spawn_event_writer: MessageWriter<SaucerSpawnEvent>,
+    time: Res<Time>,
+) {
+    spawn_timer.timer.tick(time.delta());
+
+    if !spawn_timer.timer.just_finished() {
+        return;
+    }
+
+    saucer_spawn_event_writer.write(SaucerSpawnEvent);
+}
+
+
+fn handle_saucer_spawn_event(
+    mut commands: Commands,
+    mut event_reader: MessageReader<SaucerSpawnEvent>,
+    spaceship_q: Query<&Transform, With<Spaceship>>,
+    scene_assets: Res<SceneAssets>,
+    app_globals: Res<AppGlobals>,
+    config: Res<GameConfig>,
+) {
+    let spawn_props = &ASTEROID_SIZE_PROPS[0];
+    let mut rng = rand::rng();
+
+    for _spawn_event in event_reader.read() {
+        let kind = SaucerKind::for_level(app_globals.level);
+        let saucer_props = kind.props();
+
+        let mut translation = Vec3::new(
+            rng.random_range(spawn_props.spawn_range_x.clone()),
+            0.0,
+            rng.random_range(spawn_props.spawn_range_z.clone()),
+        );
+
+        for _i in 0..2 {
+            // It is a bit unfair to have a saucer spawn right on top of a
+            // spaceship.  So we allow a (finite) number of chances to choose
+            // a different location if this happens.
+            // There is still a tiny chance of this happening, but it will be
+            // considerably less annoying.  Without this, it happens about
+            // once per game.
+            let too_close = spaceship_q
+                .iter()
+                .any(|ship| translation.distance(ship.translation) < saucer_props.radius * 4.0);
+
+            if !too_close {
+                break;
+            }
+            else {
+                translation = Vec3::new(
+                    rng.random_range(spawn_props.spawn_range_x.clone()),
+                    0.0,
+                    rng.random_range(spawn_props.spawn_range_z.clone()),
+                );
+            }
+        }
+
+        let saucer_xform = Transform::from_translation(translation)
+        .with_scale(Vec3::ONE * saucer_props.size)
+        .with_rotation(Quat::from_rotation_x(std::f32::consts::PI / 2.));
+
+        let _saucer_id = commands.spawn((
+            Name::new(saucer_props.name),
+            MovingObjectBundle {
+                velocity: Velocity::new(SAUCER_STARTING_VELOCITY),
+                acceleration: Acceleration::new(Vec3::ZERO),
+                rotation: Rotation::new(
+                    0.0,
+                    0.0,
+                    0.0
+                ),
+                collider: Collider::new(saucer_props.radius),
+                model: SceneBundle {
+                    scene: SceneRoot(scene_assets.scene("saucer")),
+                    transform: saucer_xform,
+                }
+            },
+            Saucer { kind },
+            SaucerAi::new(saucer_props.lifetime_secs),
+            Health::new(saucer_props.health),
+            CollisionDamage::new(config.saucer.collision_damage),
+        )).id();
+        
+        #[cfg(debug_assertions)]
+        info!("\tSpawned {:?} Saucer ({:?})", kind, _saucer_id);
+
+    }
+}
+
+
+fn saucer_sound_control(
+    saucers: Query<&Saucer>,
+    mut saucer_audio: Query<&mut AudioSink, With<SaucerSound>>,
+) {
+    let Ok(mut sink) = saucer_audio.single_mut() else {
+        return;
+    };
+
+    if saucers.count() > 0 {
+        sink.set_volume(Volume::Linear(1.0));
+    } else {
+        sink.set_volume(Volume::Linear(0.0));
+    }
+}
+
+
+// Everything that goes into how well a saucer shoots: it gets better as
+// the score goes up, and the difficulty and the director both have a say.
+#[derive(SystemParam)]
+struct SaucerAim<'w> {
+    config: Res<'w, GameConfig>,
+    app_globals: Res<'w, AppGlobals>,
+    difficulty: Res<'w, Difficulty>,
+    director: Res<'w, Director>,
+}
+
+impl SaucerAim<'_> {
+    // Half the width (radians) of the cone this kind of saucer's shots
+    // land in right now.
+    fn cone(&self, kind: SaucerKind) -> f32 {
+        let aim = self.config.saucer.aim(kind);
+        inaccuracy_cone(aim.max_aim_error, aim.min_aim_error, self.app_globals.score) *
+            self.difficulty.settings().saucer_aim_scalar *
+            self.director.saucer_aim_error
+    }
+}
+
+fn saucer_weapon_control(
+    mut commands: Commands,
+    mut rate_timer: ResMut<SaucerMissileRateTimer>,
+    time: Res<Time>,
+
+    saucers: Query<(Entity, &Saucer, &Transform), Without<SaucerShotWindup>>,
+    spaceship: Query<(&Transform, &Velocity), With<Spaceship>>,
+    aim: SaucerAim,
+) {
+    rate_timer.timer.tick(time.delta());
+
+    if rate_timer.timer.is_finished()
+    {
+        let mut rng = rand::rng();
+
+        for (entity, saucer, saucer_xform) in saucers.iter() {
+            // Each saucer goes after whoever is closest.
+            let Some((spaceship_xform, spaceship_velocity)) = nearest_ship(
+                saucer_xform.translation,
+                spaceship.iter(),
+            ) else {
+                continue;
+            };
+
+            let missile_chance: i32 = rng.random_range(0.0..SAUCER_MISSILE_RATE) as i32;
+            
+            if missile_chance == SAUCER_MISSILE_RATE as i32 / 2 {
+                // Get ready to shoot a missile.  We give the player a moment
+                // of warning before it actually goes off.
+                // This should fire at random times, but average about 1/s per saucer.
+                let mut direction = aim_direction(
+                    saucer.kind.props().aim_mode,
+                    saucer_xform.translation,
+                    spaceship_xform.translation,
+                    spaceship_velocity.value,
+                    aim.config.saucer.missile_speed,
+                    rng.random_range(0.0..std::f32::consts::TAU),
+                );
+
+                // Nobody is perfect.  At least not at first.  The miss is
+                // worked out now, so the warning line shows exactly where
+                // the shot will go.
+                let cone = aim.cone(saucer.kind);
+                if cone > 0.0 {
+                    direction = Quat::from_rotation_y(rng.random_range(-cone..cone)) * direction;
+                }
+
+                commands.entity(entity).insert(SaucerShotWindup::new(direction));
+            }
+        }
+    }
+}
+
+
+fn saucer_fire_after_windup(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut saucers: Query<(Entity, &Transform, &mut SaucerShotWindup), With<Saucer>>,
+    mut sound_event_writer: MessageWriter<SaucerShootingSoundEvent>,
+    missiles: MissileSpawner,
+) {
+    for (entity, saucer_xform, mut windup) in saucers.iter_mut() {
+        windup.timer.tick(time.delta());
+
+        if !windup.timer.is_finished() {
+            continue;
+        }
+
+        commands.entity(entity).remove::<SaucerShotWindup>();
+
+        // It goes exactly where the warning line said it would.
+        missiles.spawn(&mut commands, saucer_xform.translation, windup.direction);
+
+        sound_event_writer.write(SaucerShootingSoundEvent);
+    }
+}
+
+// Everything a saucer missile needs.  The boss fires the same missiles, so
+// this is shared.
+#[derive(SystemParam)]
+pub struct MissileSpawner<'w> {
+    pub scene_assets: Res<'w, SceneAssets>,
+    pub config: Res<'w, GameConfig>,
+    pub difficulty: Res<'w, Difficulty>,
+}
+
+impl MissileSpawner<'_> {
+    pub fn spawn(&self, commands: &mut Commands, origin: Vec3, direction: Vec3) {
+        let config = &self.config;
+
+        let mut missile_xform = Transform::from_translation(origin)
+            .looking_to(direction, Vec3::Z)
+            .with_scale(Vec3::ONE * SAUCER_MISSILE_SIZE);
+
+        missile_xform.translation += missile_xform.forward() * SAUCER_MISSILE_FORWARD_SPAWN_SCALAR;
+
+        commands.spawn((
+            Name::new("saucer_missile"),
+            MovingObjectBundle {
+                velocity: Velocity::new(missile_xform.forward() * config.saucer.missile_speed),
+                acceleration: Acceleration::new(Vec3::ZERO),
+                rotation: Rotation::new(0.0, 0.0, 0.0),
+                collider: Collider::new(SAUCER_MISSILE_RADIUS),
+                model: SceneBundle {
+                    scene: SceneRoot(self.scene_assets.scene("saucer_missile")),
+                    transform: missile_xform,
+                },
+            },
+            SaucerMissile,
+            Health::new(config.saucer.missile_health),
+            CollisionDamage::new(self.difficulty.settings().saucer_missile_damage),
+            MaxRange::new(config.saucer.missile_range),
+        ));
+    }
+}
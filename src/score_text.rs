@@ -4,8 +4,13 @@ use crate::{
     app_globals::AppGlobals,
     asset_loader::SceneAssets,
     asteroids::Asteroid,
+    difficulty::Difficulty,
     health::Health,
-    state::GameState
+    modes::GameMode,
+    players::{LastHitBy, TeamScore},
+    saucer::Saucer,
+    schedule::InGameSet,
+    state::{GameSetup, GameState}
 };
 
 #[derive(Component, Debug)]
@@ -17,14 +22,27 @@ pub struct LastScore;
 #[derive(Component, Debug)]
 pub struct HighScore;
 
+#[derive(Component, Debug)]
+pub struct Level;
+
+// The score texts are all TextSpans, so each query has to rule out the
+// others.
+type HighScoreOnly = (With<HighScore>, Without<Score>, Without<LastScore>);
+
 pub struct ScorePlugin;
 
 impl Plugin for ScorePlugin {
     fn build(&self, app: &mut App) {
         app
-        .add_systems(PostStartup, spawn_score)
+        .add_systems(GameSetup, (spawn_score, update_high_score).chain())
         .add_systems(OnEnter(GameState::GameOver), reset_score)
-        .add_systems(Update, update_score);
+        // Anything that died is despawned in this set, so each kill is only
+        // seen (and paid for) once.
+        .add_systems(Update, award_kill_points.in_set(InGameSet::DespawnEntities))
+        .add_systems(Update, (
+            update_score,
+            update_high_score.run_if(resource_changed::<Difficulty>.or(resource_changed::<GameMode>)),
+        ));
     }
 }
 
@@ -49,7 +67,7 @@ fn spawn_score(
         Text::new("Score: "),
         TextFont {
             // This font is loaded and will be used instead of the default font.
-            font: scene_assets.font.clone(),
+            font: scene_assets.font("font"),
             font_size: 22.0,
             ..default()
         },
@@ -59,7 +77,7 @@ fn spawn_score(
         (
             // "default_font" feature is unavailable, load a font to use instead.
             TextFont {
-                font: scene_assets.font.clone(),
+                font: scene_assets.font("font"),
                 font_size: 22.0,
                 ..Default::default()
             },
@@ -67,6 +85,38 @@ fn spawn_score(
         Score,
     ));
 
+    commands.spawn((
+        Name::new("level_text"),
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Percent(7.0),
+            left: Val::Percent(2.0),
+            width: Val::Percent(22.),
+            height: Val::Percent(5.),
+            display: Display::Flex,
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::Center,
+            ..default()
+        },
+        Text::new("Level: "),
+        TextFont {
+            font: scene_assets.font("font"),
+            font_size: 22.0,
+            ..default()
+        },
+    ))
+    .with_child((
+        TextSpan::new("1"),
+        (
+            TextFont {
+                font: scene_assets.font("font"),
+                font_size: 22.0,
+                ..Default::default()
+            },
+        ),
+        Level,
+    ));
+
     commands.spawn((
         Name::new("high_score_text"),
         // We could also use a `UiTargetCamera` component here instead of the general `IsDefaultUiCamera`.
@@ -84,7 +134,7 @@ fn spawn_score(
         //BackgroundColor(Color::linear_rgba(0.9843137, 0.44313726, 0.52156866, 0.1)),
         Text::new("High Score: "),
         TextFont {
-            font: scene_assets.font.clone(),
+            font: scene_assets.font("font"),
             font_size: 33.0,
             ..Default::default()
         },
@@ -94,7 +144,7 @@ fn spawn_score(
         (
             // "default_font" feature is unavailable, load a font to use instead.
             TextFont { 
-                font: scene_assets.font.clone(),
+                font: scene_assets.font("font"),
                 font_size: 33.0,
                 ..Default::default()
             },
@@ -119,7 +169,7 @@ fn spawn_score(
         //BackgroundColor(Color::linear_rgba(0.9843137, 0.44313726, 0.52156866, 0.1)),
         Text::new("Last Score: "),
         TextFont {
-            font: scene_assets.font.clone(),
+            font: scene_assets.font("font"),
             font_size: 22.0,
             ..Default::default()
         },
@@ -129,7 +179,7 @@ fn spawn_score(
         (
             // "default_font" feature is unavailable, load a font to use instead.
             TextFont { 
-                font: scene_assets.font.clone(),
+                font: scene_assets.font("font"),
                 font_size: 22.0,
                 ..Default::default()
             },
@@ -139,29 +189,56 @@ fn spawn_score(
 
 }
 
-fn update_score(
-    mut query: Query<&mut TextSpan, With<Score>>,
-    asteroid_query: Query<&Health, With<Asteroid>>,
-    mut app_globals: ResMut<AppGlobals>,
+fn award_kill_points(
+    asteroid_query: Query<(&Health, &Asteroid, Option<&LastHitBy>)>,
+    saucer_query: Query<(&Health, &Saucer, Option<&LastHitBy>)>,
+    mut team_score: TeamScore,
 ) {
-    let Ok(mut span,) = query.single_mut() else {
-        return;
+    // The player who got the last hit in gets the points.  If nobody did
+    // (it ran into something), the team shares them.
+    let mut award = |points: i32, hit: Option<&LastHitBy>| match hit {
+        Some(hit) => team_score.award(hit.0, points),
+        None => team_score.share(points),
     };
 
-    for health in asteroid_query.iter() {
+    for (health, asteroid, hit) in asteroid_query.iter() {
+        if health.value <= 0.0 {
+            award(asteroid.variant.props().points, hit);
+        }
+    }
+
+    for (health, saucer, hit) in saucer_query.iter() {
         if health.value <= 0.0 {
-            app_globals.score += 1;
+            award(saucer.kind.props().points, hit);
         }
     }
+}
+
+fn update_score(
+    mut query: Query<&mut TextSpan, With<Score>>,
+    mut level_query: Query<&mut TextSpan, (With<Level>, Without<Score>)>,
+    app_globals: Res<AppGlobals>,
+) {
+    let Ok(mut span,) = query.single_mut() else {
+        return;
+    };
 
     **span = format!("{:}", app_globals.score);
+
+    // The level has already moved on to the next wave by the time this one
+    // is in play.
+    if let Ok(mut level_span) = level_query.single_mut() {
+        **level_span = format!("{:}", (app_globals.level - 1).max(1));
+    }
 }
 
 fn reset_score(
     mut score_query: Query<&mut TextSpan, With<Score>>,
     mut last_score_query: Query<&mut TextSpan, (With<LastScore>, Without<Score>)>,
-    mut high_score_query: Query<&mut TextSpan, (With<HighScore>, Without<Score>, Without<LastScore>)>,
+    mut high_score_query: Query<&mut TextSpan, HighScoreOnly>,
     mut app_globals: ResMut<AppGlobals>,
+    difficulty: Res<Difficulty>,
+    mode: Res<GameMode>,
 ) {
     let Ok(mut score_span) = score_query.single_mut() else {
         return;
@@ -175,9 +252,33 @@ fn reset_score(
         return;
     };
 
-    app_globals.final_score_update();
+    app_globals.final_score_update((*mode, difficulty.level));
 
     **last_score_span = format!("{:}", app_globals.last_score);
-    **high_score_span = format!("{:}", app_globals.high_score);
+    **high_score_span = high_score_text(&app_globals, &mode, &difficulty);
     **score_span = format!("{:}", app_globals.score);
 }
+
+// Each mode and difficulty has its own high score, and we show the one for
+// what is being played.
+fn update_high_score(
+    mut high_score_query: Query<&mut TextSpan, With<HighScore>>,
+    app_globals: Res<AppGlobals>,
+    difficulty: Res<Difficulty>,
+    mode: Res<GameMode>,
+) {
+    let Ok(mut high_score_span) = high_score_query.single_mut() else {
+        return;
+    };
+
+    **high_score_span = high_score_text(&app_globals, &mode, &difficulty);
+}
+
+fn high_score_text(app_globals: &AppGlobals, mode: &GameMode, difficulty: &Difficulty) -> String {
+    format!(
+        "{:} ({}, {})",
+        app_globals.high_score((*mode, difficulty.level)),
+        mode.name(),
+        difficulty.level.name(),
+    )
+}
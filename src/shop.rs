@@ -0,0 +1,274 @@
+use bevy::prelude::*;
+
+use crate::{
+    asset_loader::SceneAssets,
+    config::GameConfig,
+    game_over::mute_thruster_sound,
+    ore::OreWallet,
+    spaceship::{
+        upgrades::{ShipUpgrades, UpgradeKind, UPGRADE_MAX_LEVEL},
+        Spaceship,
+    },
+    state::GameState,
+};
+
+const SHOP_KEYS: [KeyCode; 7] = [
+    KeyCode::Digit1,
+    KeyCode::Digit2,
+    KeyCode::Digit3,
+    KeyCode::Digit4,
+    KeyCode::Digit5,
+    KeyCode::Digit6,
+    KeyCode::Digit7,
+];
+
+const SHOP_TEXT_COLOR: Color = Color::srgb(0.9, 0.9, 0.9);
+const SHOP_DIM_COLOR: Color = Color::srgb(0.45, 0.45, 0.45);
+const SHOP_CREDITS_COLOR: Color = Color::srgb(1.0, 0.8, 0.1);
+
+
+// Whether the shop shows up between waves (from the config), and which wave
+// it was last opened before, so that it only opens once per wave.
+#[derive(Resource, Debug)]
+pub struct ShopSettings {
+    pub enabled: bool,
+    pub visited_level: i32,
+}
+
+impl Default for ShopSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            visited_level: 0,
+        }
+    }
+}
+
+impl ShopSettings {
+    // Asteroids calls this when a wave has been cleared, just before it
+    // launches the next one.  Returns true if we went to the shop instead.
+    pub fn open_before_wave(
+        &mut self,
+        level: i32,
+        wallet: &OreWallet,
+        upgrades: &ShipUpgrades,
+        next_state: &mut NextState<GameState>,
+    ) -> bool {
+        // No shop before the very first wave, and no point opening it if
+        // there is nothing we can afford.
+        if !self.enabled || level <= 1 || self.visited_level == level {
+            return false;
+        }
+
+        self.visited_level = level;
+
+        let affordable = UpgradeKind::ALL
+            .iter()
+            .filter_map(|kind| upgrades.cost(*kind))
+            .any(|cost| cost <= wallet.credits);
+
+        if affordable {
+            next_state.set(GameState::Shop);
+        }
+
+        affordable
+    }
+}
+
+#[derive(Component, Debug)]
+pub struct ShopDlg;
+
+#[derive(Component, Debug)]
+pub struct ShopCreditsText;
+
+#[derive(Component, Debug)]
+pub struct ShopItemText {
+    pub kind: UpgradeKind,
+}
+
+
+pub struct ShopPlugin;
+
+impl Plugin for ShopPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ShopSettings>()
+        .add_systems(OnEnter(GameState::Shop), (
+            spawn_shop_dlg,
+            mute_thruster_sound,
+        ))
+        .add_systems(OnExit(GameState::Shop), despawn_shop_dlg)
+        .add_systems(OnEnter(GameState::StartGame), reset_shop)
+        .add_systems(Update, apply_shop_config.run_if(resource_changed::<GameConfig>))
+        .add_systems(Update, (
+                shop_input,
+                update_shop_dlg,
+            )
+            .chain()
+            .run_if(in_state(GameState::Shop)),
+        );
+    }
+}
+
+fn spawn_shop_dlg(
+    mut commands: Commands,
+    scene_assets: Res<SceneAssets>,
+) {
+    let font = |size: f32| TextFont {
+        font: scene_assets.font("font"),
+        font_size: size,
+        ..default()
+    };
+
+    commands.spawn((
+        Name::new("shop_dlg"),
+        Node {
+            width: Val::Percent(100.),
+            height: Val::Percent(100.),
+            flex_direction: FlexDirection::Column,
+            align_items: AlignItems::Center,
+            justify_content: JustifyContent::Center,
+            row_gap: Val::Px(6.0),
+            ..default()
+        },
+        BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.6)),
+        GlobalZIndex(10),
+        ShopDlg,
+    ))
+    .with_children(|builder| {
+        builder.spawn((
+            Text::new("Wave cleared - Upgrade Shop"),
+            font(40.0),
+        ));
+
+        builder.spawn((
+            Text::new(""),
+            font(22.0),
+            TextColor(SHOP_CREDITS_COLOR),
+            ShopCreditsText,
+        ));
+
+        // One line per upgrade, filled in by update_shop_dlg.
+        for kind in UpgradeKind::ALL {
+            builder.spawn((
+                Text::new(""),
+                font(20.0),
+                TextColor(SHOP_TEXT_COLOR),
+                ShopItemText { kind },
+            ));
+        }
+
+        builder.spawn((
+            Node {
+                margin: UiRect::top(Val::Px(16.0)),
+                ..default()
+            },
+            Text::new("Press 1-7 to buy.  Press <Enter> to launch the next wave."),
+            font(18.0),
+        ));
+    });
+}
+
+fn despawn_shop_dlg(
+    mut commands: Commands,
+    query: Query<Entity, With<ShopDlg>>,
+) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn();
+    }
+}
+
+fn reset_shop(
+    mut settings: ResMut<ShopSettings>,
+) {
+    settings.visited_level = 0;
+}
+
+fn apply_shop_config(
+    config: Res<GameConfig>,
+    mut settings: ResMut<ShopSettings>,
+) {
+    if settings.enabled != config.shop.enabled {
+        settings.enabled = config.shop.enabled;
+    }
+}
+
+fn shop_input(
+    mut next_state: ResMut<NextState<GameState>>,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut wallet: ResMut<OreWallet>,
+    mut ship_q: Query<&mut ShipUpgrades, With<Spaceship>>,
+) {
+    if keyboard_input.just_pressed(KeyCode::Enter) {
+        next_state.set(GameState::InGame);
+        return;
+    }
+
+    // In co-op the ore and the upgrades are shared, so every ship gets what
+    // is bought, and they all have the same levels to price it by.
+    let Some(levels) = ship_q.iter().next().cloned() else {
+        return;
+    };
+
+    for (key, kind) in SHOP_KEYS.iter().zip(UpgradeKind::ALL) {
+        if !keyboard_input.just_pressed(*key) {
+            continue;
+        }
+
+        let Some(cost) = levels.cost(kind) else {
+            continue;
+        };
+
+        if cost <= wallet.credits {
+            wallet.credits -= cost;
+            for mut upgrades in ship_q.iter_mut() {
+                upgrades.upgrade(kind);
+            }
+
+            #[cfg(debug_assertions)]
+            info!("Bought {:?} upgrade for {} credits", kind, cost);
+        }
+    }
+}
+
+fn update_shop_dlg(
+    wallet: Res<OreWallet>,
+    ship_q: Query<&ShipUpgrades, With<Spaceship>>,
+    mut credits_q: Query<&mut Text, With<ShopCreditsText>>,
+    mut items_q: Query<(&ShopItemText, &mut Text, &mut TextColor), Without<ShopCreditsText>>,
+) {
+    let Some(upgrades) = ship_q.iter().next() else {
+        return;
+    };
+
+    for mut text in credits_q.iter_mut() {
+        text.0 = format!("Ore credits: {}", wallet.credits);
+    }
+
+    for (item, mut text, mut color) in items_q.iter_mut() {
+        let level = upgrades.level(item.kind);
+
+        let price = match upgrades.cost(item.kind) {
+            Some(cost) => format!("{} credits", cost),
+            None => "maxed out".to_string(),
+        };
+
+        // The query doesn't come back in spawn order, so work out the key
+        // from the upgrade itself.
+        let key = UpgradeKind::ALL
+            .iter()
+            .position(|kind| *kind == item.kind)
+            .unwrap_or_default() + 1;
+
+        text.0 = format!(
+            "{}. {:<18} Lv {}/{}   {}",
+            key,
+            item.kind.name(),
+            level,
+            UPGRADE_MAX_LEVEL,
+            price,
+        );
+
+        let affordable = upgrades.cost(item.kind).is_some_and(|cost| cost <= wallet.credits);
+        color.0 = if affordable { SHOP_TEXT_COLOR } else { SHOP_DIM_COLOR };
+    }
+}
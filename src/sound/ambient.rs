@@ -1,7 +1,10 @@
 use bevy::prelude::*;
 use bevy::audio::{PlaybackMode, Volume};
 
-use crate::asset_loader::SceneAssets;
+use crate::{
+    asset_loader::SceneAssets,
+    state::GameSetup,
+};
 
 
 #[derive(Component, Debug)]
@@ -18,7 +21,7 @@ pub struct AmbientSoundPlugin;
 impl Plugin for AmbientSoundPlugin {
     fn build(&self, app: &mut App) {
         app
-        .add_systems(Startup, spawn_ambient_sound);
+        .add_systems(GameSetup, spawn_ambient_sound);
     }
 }
 
@@ -29,7 +32,7 @@ fn spawn_ambient_sound(
 ) {
     commands.spawn((
         Name::new("background_music"),
-        AudioPlayer::new(scene_assets.background_music.clone()),
+        AudioPlayer::new(scene_assets.audio("background_music")),
         PlaybackSettings {
             mode: PlaybackMode::Loop,
             volume: Volume::Linear(1.0),
@@ -40,7 +43,7 @@ fn spawn_ambient_sound(
 
     commands.spawn((
         Name::new("thruster_sound"),
-        AudioPlayer::new(scene_assets.thruster_sound.clone()),
+        AudioPlayer::new(scene_assets.audio("thruster_sound")),
         PlaybackSettings {
             mode: PlaybackMode::Loop,
             volume: Volume::Linear(1.0),
@@ -51,7 +54,7 @@ fn spawn_ambient_sound(
 
     commands.spawn((
         Name::new("soucer_sound"),
-        AudioPlayer::new(scene_assets.saucer_sound.clone()),
+        AudioPlayer::new(scene_assets.audio("saucer_sound")),
         PlaybackSettings {
             mode: PlaybackMode::Loop,
             volume: Volume::Linear(1.0),
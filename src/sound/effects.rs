@@ -3,7 +3,7 @@ use bevy::{audio, prelude::*};
 use crate::{
     asset_loader::SceneAssets,
     schedule::InGameSet,
-    spaceship::shield::ShieldReadyEvent,
+    spaceship::shield::{ShieldHitEvent, ShieldReadyEvent},
 };
 
 const SOUND_EFFECTS_VOLUME: audio::Volume = audio::Volume::Linear(0.8);
@@ -17,10 +17,12 @@ pub struct GameSoundEffects {
 }
 
 
-// in the future, we will have multiple sounds.  It might make sense
-// for the type of sound to be an enum and stored in the event.
+// Each weapon has its own sound, so the event carries the sound's
+// asset manifest id.
 #[derive(Message, Debug)]
-pub struct ShootingSoundEvent;
+pub struct ShootingSoundEvent {
+    pub sound: &'static str,
+}
 
 #[derive(Message, Debug)]
 pub struct SaucerShootingSoundEvent;
@@ -41,12 +43,14 @@ impl Plugin for SoundFXPlugin {
                 play_saucer_shooting_sound,
                 play_meteor_collision_sound,
                 play_shield_ready_sound,
+                play_shield_hit_sound,
                 set_sound_fx_volume,
             ).in_set(InGameSet::EntityUpdates),
         )
         .add_message::<ShootingSoundEvent>()
         .add_message::<SaucerShootingSoundEvent>()
         .add_message::<ShieldReadyEvent>()
+        .add_message::<ShieldHitEvent>()
         .add_message::<AsteroidCollisionSoundEvent>();
     }
 }
@@ -67,10 +71,10 @@ fn play_shooting_sound(
     mut commands: Commands,
     scene_assets: Res<SceneAssets>,
 ) {
-    for _ in sound_event_reader.read() {
+    for event in sound_event_reader.read() {
         commands.spawn((
             Name::new("shooting_sound"),
-            AudioPlayer::new(scene_assets.shooting_sound.clone()),
+            AudioPlayer::new(scene_assets.audio(event.sound)),
             GameSoundEffects {
                 volume_is_set: false,
                 volume: SOUND_EFFECTS_VOLUME,
@@ -87,7 +91,7 @@ fn play_saucer_shooting_sound(
     for _ in sound_event_reader.read() {
         commands.spawn((
             Name::new("saucer_shooting_sound"),
-            AudioPlayer::new(scene_assets.saucer_shooting_sound.clone()),
+            AudioPlayer::new(scene_assets.audio("saucer_shooting_sound")),
             GameSoundEffects {
                 volume_is_set: false,
                 volume: SOUND_EFFECTS_VOLUME,
@@ -104,7 +108,7 @@ fn play_meteor_collision_sound(
     for _ in sound_event_reader.read() {
         commands.spawn((
             Name::new("meteor_hit_sound"),
-            AudioPlayer::new(scene_assets.meteor_hit_sound.clone()),
+            AudioPlayer::new(scene_assets.audio("meteor_hit_sound")),
             GameSoundEffects {
                 volume_is_set: false,
                 volume: SOUND_EFFECTS_VOLUME,
@@ -124,7 +128,7 @@ fn play_shield_ready_sound(
 
         commands.spawn((
             Name::new("shield_ready_sound"),
-            AudioPlayer::new(scene_assets.shield_ready_sound.clone()),
+            AudioPlayer::new(scene_assets.audio("shield_ready_sound")),
             GameSoundEffects {
                 volume_is_set: false,
                 volume: SHIELD_READY_VOLUME,
@@ -136,6 +140,23 @@ fn play_shield_ready_sound(
     }
 }
 
+fn play_shield_hit_sound(
+    mut reader: MessageReader<ShieldHitEvent>,
+    mut commands: Commands,
+    scene_assets: Res<SceneAssets>,
+) {
+    for _ in reader.read() {
+        commands.spawn((
+            Name::new("shield_hit_sound"),
+            AudioPlayer::new(scene_assets.audio("shield_hit_sound")),
+            GameSoundEffects {
+                volume_is_set: false,
+                volume: SOUND_EFFECTS_VOLUME,
+            },
+        ));
+    }
+}
+
 fn set_sound_fx_volume(
     mut query: Query<(&mut AudioSink, &mut GameSoundEffects)>,
 ) {
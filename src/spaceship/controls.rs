@@ -0,0 +1,155 @@
+use bevy::prelude::*;
+
+use crate::players::{Player, PlayerCount};
+use super::Spaceship;
+
+
+// Which keys fly which ship.  On your own you get both sets of movement
+// keys, like always.  In co-op the keyboard is split down the middle.
+pub struct KeyBindings {
+    pub thrust: &'static [KeyCode],
+    pub reverse: &'static [KeyCode],
+    pub turn_left: &'static [KeyCode],
+    pub turn_right: &'static [KeyCode],
+    pub roll_left: &'static [KeyCode],
+    pub roll_right: &'static [KeyCode],
+    pub fire: &'static [KeyCode],
+    pub shield: &'static [KeyCode],
+    pub hyperspace: &'static [KeyCode],
+    pub cycle_weapon: &'static [KeyCode],
+}
+
+const SOLO_KEYS: KeyBindings = KeyBindings {
+    thrust: &[KeyCode::KeyW, KeyCode::ArrowUp],
+    reverse: &[KeyCode::KeyS, KeyCode::ArrowDown],
+    turn_left: &[KeyCode::KeyA, KeyCode::ArrowLeft],
+    turn_right: &[KeyCode::KeyD, KeyCode::ArrowRight],
+    roll_left: &[KeyCode::ShiftLeft],
+    roll_right: &[KeyCode::ControlLeft],
+    fire: &[KeyCode::Space],
+    shield: &[KeyCode::Tab],
+    hyperspace: &[KeyCode::KeyH],
+    cycle_weapon: &[KeyCode::KeyE],
+};
+
+const PLAYER_1_KEYS: KeyBindings = KeyBindings {
+    thrust: &[KeyCode::KeyW],
+    reverse: &[KeyCode::KeyS],
+    turn_left: &[KeyCode::KeyA],
+    turn_right: &[KeyCode::KeyD],
+    ..SOLO_KEYS
+};
+
+const PLAYER_2_KEYS: KeyBindings = KeyBindings {
+    thrust: &[KeyCode::ArrowUp],
+    reverse: &[KeyCode::ArrowDown],
+    turn_left: &[KeyCode::ArrowLeft],
+    turn_right: &[KeyCode::ArrowRight],
+    roll_left: &[KeyCode::KeyK],
+    roll_right: &[KeyCode::KeyL],
+    fire: &[KeyCode::ControlRight],
+    shield: &[KeyCode::ShiftRight],
+    hyperspace: &[KeyCode::Slash],
+    cycle_weapon: &[KeyCode::Period],
+};
+
+// For the game over screen.
+pub const PLAYER_1_KEYS_HELP: &str = "P1: WASD, <Space> fire, <Tab> shield, H hyperspace, E weapon";
+pub const PLAYER_2_KEYS_HELP: &str = "P2: arrows, <Right Ctrl> fire, <Right Shift> shield, / hyperspace, . weapon";
+
+fn key_bindings(player: Player, player_count: PlayerCount) -> &'static KeyBindings {
+    match (player_count.is_coop(), player.0) {
+        (false, _) => &SOLO_KEYS,
+        (true, 0) => &PLAYER_1_KEYS,
+        (true, _) => &PLAYER_2_KEYS,
+    }
+}
+
+
+// What a ship's pilot wants it to do this frame, from the keyboard and
+// their gamepad (if they have one).  The control systems only look at this,
+// so they don't need to know who is flying what.
+#[derive(Component, Debug, Default)]
+pub struct ShipInput {
+    pub thrust: f32,  // -1.0 (reverse) to 1.0
+    pub turn: f32,  // -1.0 (right) to 1.0 (left)
+    pub roll: f32,
+    pub fire: bool,  // held down
+    // Just pressed.
+    pub shield: bool,
+    pub hyperspace: bool,
+    pub cycle_weapon: bool,
+}
+
+impl ShipInput {
+    fn from_keys(keys: &KeyBindings, keyboard_input: &ButtonInput<KeyCode>) -> Self {
+        // As always, if both are held the first one wins.
+        let axis = |negative: &[KeyCode], positive: &[KeyCode]| {
+            if keyboard_input.any_pressed(negative.iter().copied()) {
+                -1.0
+            }
+            else if keyboard_input.any_pressed(positive.iter().copied()) {
+                1.0
+            }
+            else {
+                0.0
+            }
+        };
+
+        Self {
+            thrust: axis(keys.reverse, keys.thrust),
+            turn: axis(keys.turn_right, keys.turn_left),
+            roll: axis(keys.roll_left, keys.roll_right),
+            fire: keyboard_input.any_pressed(keys.fire.iter().copied()),
+            shield: keyboard_input.any_just_pressed(keys.shield.iter().copied()),
+            hyperspace: keyboard_input.any_just_pressed(keys.hyperspace.iter().copied()),
+            cycle_weapon: keyboard_input.any_just_pressed(keys.cycle_weapon.iter().copied()),
+        }
+    }
+
+    // Left stick (or d-pad) to fly, bumpers to roll, A to fire, B for the
+    // shield, Y for hyperspace and X to change weapons.
+    fn add_gamepad(&mut self, gamepad: &Gamepad) {
+        let stick = gamepad.left_stick() + gamepad.dpad();
+
+        if self.thrust == 0.0 {
+            self.thrust = stick.y.clamp(-1.0, 1.0);
+        }
+        if self.turn == 0.0 {
+            self.turn = (-stick.x).clamp(-1.0, 1.0);
+        }
+        if self.roll == 0.0 {
+            if gamepad.pressed(GamepadButton::LeftTrigger) {
+                self.roll = -1.0;
+            }
+            else if gamepad.pressed(GamepadButton::RightTrigger) {
+                self.roll = 1.0;
+            }
+        }
+
+        self.fire |= gamepad.any_pressed([GamepadButton::South, GamepadButton::RightTrigger2]);
+        self.shield |= gamepad.just_pressed(GamepadButton::East);
+        self.hyperspace |= gamepad.just_pressed(GamepadButton::North);
+        self.cycle_weapon |= gamepad.just_pressed(GamepadButton::West);
+    }
+}
+
+// Gamepads are handed out in the order they were connected: the first one
+// is player 1's, the second player 2's.
+pub fn read_ship_input(
+    mut ship_q: Query<(&Player, &mut ShipInput), With<Spaceship>>,
+    gamepads: Query<(Entity, &Gamepad)>,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    player_count: Res<PlayerCount>,
+) {
+    let mut gamepads = gamepads.iter().collect::<Vec<_>>();
+    gamepads.sort_by_key(|(entity, _)| *entity);
+
+    for (player, mut input) in ship_q.iter_mut() {
+        *input = ShipInput::from_keys(key_bindings(*player, *player_count), &keyboard_input);
+
+        if let Some((_, gamepad)) = gamepads.get(player.0) {
+            input.add_gamepad(gamepad);
+        }
+    }
+}
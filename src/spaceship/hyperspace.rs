@@ -0,0 +1,260 @@
+use std::f32::consts::{FRAC_PI_2, TAU};
+
+use bevy::prelude::*;
+use rand::Rng;
+
+use crate::{
+    asteroids::Asteroid,
+    collision_detection::Collider,
+    health::Health,
+    movement::{play_area_bounds, random_play_area_position, Acceleration, Velocity},
+    schedule::InGameSet,
+};
+use super::{
+    shield::consume_shield_request,
+    ShieldController,
+    ShieldRequestEvent,
+    ShieldState,
+    Spaceship,
+    SPACESHIP_RADIUS,
+    SPACESHIP_SIZE,
+};
+
+const HYPERSPACE_COOLDOWN_SECS: f32 = 12.0;
+const HYPERSPACE_WARP_SECS: f32 = 0.3;  // for each of warp out & warp in
+
+// Every so often the hyperdrive doesn't quite work, and the ship comes out
+// of hyperspace damaged and tumbling.
+const HYPERSPACE_MALFUNCTION_CHANCE: f64 = 0.12;
+const HYPERSPACE_MALFUNCTION_DAMAGE: f32 = 40.0;
+
+// Keep away from the very edge of the screen, and try not to land right on
+// top of an asteroid.
+const HYPERSPACE_EDGE_MARGIN: f32 = 5.0;
+const HYPERSPACE_SAFE_DISTANCE: f32 = SPACESHIP_RADIUS * 4.0;
+const HYPERSPACE_PLACEMENT_TRIES: usize = 8;
+
+const WARP_RING_RADIUS: f32 = 6.0;
+const WARP_COLOR: Color = Color::srgba(0.5, 0.8, 1.0, 0.9);
+const WARP_MALFUNCTION_COLOR: Color = Color::srgba(1.0, 0.4, 0.2, 0.9);
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HyperspaceState {
+    Ready,
+    WarpOut,
+    WarpIn,
+    Cooldown,
+}
+
+#[derive(Component, Debug)]
+pub struct HyperspaceController {
+    pub state: HyperspaceState,
+    pub cooldown: Timer,
+    pub warp: Timer,
+    pub departure: Vec3,
+    pub malfunction: bool,
+}
+
+impl Default for HyperspaceController {
+    fn default() -> Self {
+        let mut cooldown = Timer::from_seconds(HYPERSPACE_COOLDOWN_SECS, TimerMode::Once);
+        cooldown.set_elapsed(cooldown.duration()); // mark finished
+
+        Self {
+            state: HyperspaceState::Ready,
+            cooldown,
+            warp: Timer::from_seconds(HYPERSPACE_WARP_SECS, TimerMode::Once),
+            departure: Vec3::ZERO,
+            malfunction: false,
+        }
+    }
+}
+
+// On the ship while it is warping out or in.  It isn't really there, so it
+// can't be flown, fire or be hit.
+#[derive(Component, Debug)]
+pub struct Warping;
+
+// A ship that is actually there to be flown.
+pub type FlyableShip = (With<Spaceship>, Without<Warping>);
+
+// message for requesting a hyperspace jump during gameplay
+#[derive(Message, Debug)]
+pub struct HyperspaceRequestEvent {
+    pub ship: Entity,
+}
+
+
+pub struct HyperspacePlugin;
+
+impl Plugin for HyperspacePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_message::<HyperspaceRequestEvent>()
+        .add_systems(Update,
+            consume_hyperspace_request
+                .before(consume_shield_request)
+                .in_set(InGameSet::UserInput),
+        )
+        .add_systems(Update, (
+                update_hyperspace,
+                draw_warp_effect,
+            )
+            .chain()
+            .in_set(InGameSet::EntityUpdates),
+        );
+    }
+}
+
+
+fn consume_hyperspace_request(
+    mut commands: Commands,
+    mut request_reader: MessageReader<HyperspaceRequestEvent>,
+    mut shield_request_writer: MessageWriter<ShieldRequestEvent>,
+    mut ship_q: Query<
+        (Entity, &Transform, &mut Acceleration, &mut HyperspaceController, &ShieldController),
+        With<Spaceship>,
+    >,
+) {
+    for &HyperspaceRequestEvent { ship } in request_reader.read() {
+        let Ok((ship_e, xform, mut acceleration, mut controller, shield)) = ship_q.get_mut(ship) else { continue; };
+
+        if controller.state != HyperspaceState::Ready {
+            #[cfg(debug_assertions)]
+            info!("Hyperspace requested while {:?} (ignored)", controller.state);
+            continue;
+        }
+
+        controller.state = HyperspaceState::WarpOut;
+        controller.departure = xform.translation;
+        controller.warp.reset();
+
+        // Nothing can hit us while we are in hyperspace, and we can't do
+        // anything either.
+        commands.entity(ship_e).remove::<Collider>().insert(Warping);
+        acceleration.value = Vec3::ZERO;
+
+        // The shield doesn't come with us, so drop it (and bank its charge)
+        // rather than leave it behind taking hits.
+        if shield.state == ShieldState::Active {
+            shield_request_writer.write(ShieldRequestEvent { ship: ship_e });
+        }
+
+        #[cfg(debug_assertions)]
+        info!("Hyperspace: Ready -> WarpOut");
+    }
+}
+
+fn update_hyperspace(
+    mut commands: Commands,
+    mut ship_q: Query<
+        (Entity, &mut HyperspaceController, &mut Transform, &mut Velocity, &mut Health),
+        With<Spaceship>,
+    >,
+    asteroids: Query<&Transform, (With<Asteroid>, Without<Spaceship>)>,
+    camera_query: Query<&Projection, With<Camera>>,
+    time: Res<Time>,
+) {
+    let projection = camera_query.single().ok();
+
+    for (ship_e, mut controller, mut xform, mut velocity, mut health) in ship_q.iter_mut() {
+        match controller.state {
+            HyperspaceState::Ready => {}
+            HyperspaceState::WarpOut => {
+                controller.warp.tick(time.delta());
+                xform.scale = Vec3::ONE * SPACESHIP_SIZE * (1.0 - controller.warp.fraction());
+
+                if !controller.warp.is_finished() {
+                    continue;
+                }
+
+                let Some(projection) = projection else { continue; };
+                let (min, max) = play_area_bounds(projection);
+
+                let mut rng = rand::rng();
+                let mut random_position = || random_play_area_position(&mut rng, min, max, HYPERSPACE_EDGE_MARGIN);
+
+                let mut destination = random_position();
+                for _ in 0..HYPERSPACE_PLACEMENT_TRIES {
+                    let too_close = asteroids.iter().any(|asteroid| {
+                        asteroid.translation.distance(destination) < HYPERSPACE_SAFE_DISTANCE
+                    });
+                    if !too_close {
+                        break;
+                    }
+                    destination = random_position();
+                }
+
+                xform.translation = destination;
+                velocity.value = Vec3::ZERO;
+
+                controller.malfunction = rand::rng().random_bool(HYPERSPACE_MALFUNCTION_CHANCE);
+                if controller.malfunction {
+                    // Come out facing some random direction, a bit worse for wear.
+                    xform.rotation = Quat::from_rotation_y(rand::rng().random_range(0.0..TAU));
+                    health.value -= HYPERSPACE_MALFUNCTION_DAMAGE;
+
+                    #[cfg(debug_assertions)]
+                    info!("Hyperspace malfunction!");
+                }
+
+                controller.state = HyperspaceState::WarpIn;
+                controller.warp.reset();
+
+                #[cfg(debug_assertions)]
+                info!("Hyperspace: WarpOut -> WarpIn at {:?}", destination);
+            }
+            HyperspaceState::WarpIn => {
+                controller.warp.tick(time.delta());
+                xform.scale = Vec3::ONE * SPACESHIP_SIZE * controller.warp.fraction();
+
+                if controller.warp.is_finished() {
+                    commands.entity(ship_e).insert(Collider::new(SPACESHIP_RADIUS)).remove::<Warping>();
+                    controller.state = HyperspaceState::Cooldown;
+                    controller.cooldown.reset();
+
+                    #[cfg(debug_assertions)]
+                    info!("Hyperspace: WarpIn -> Cooldown");
+                }
+            }
+            HyperspaceState::Cooldown => {
+                controller.cooldown.tick(time.delta());
+
+                if controller.cooldown.just_finished() {
+                    controller.state = HyperspaceState::Ready;
+
+                    #[cfg(debug_assertions)]
+                    info!("Hyperspace: Cooldown -> Ready");
+                }
+            }
+        }
+    }
+}
+
+fn draw_warp_effect(
+    mut gizmos: Gizmos,
+    ship_q: Query<(&Transform, &HyperspaceController), With<Spaceship>>,
+) {
+    let flat = Quat::from_rotation_x(FRAC_PI_2);
+
+    for (xform, controller) in ship_q.iter() {
+        let t = controller.warp.fraction();
+
+        // Rings collapse in on the ship as it leaves, and burst out from where it
+        // arrives.
+        let (position, radius, color) = match controller.state {
+            HyperspaceState::WarpOut => (controller.departure, WARP_RING_RADIUS * (1.0 - t), WARP_COLOR),
+            HyperspaceState::WarpIn => (
+                xform.translation,
+                WARP_RING_RADIUS * t,
+                if controller.malfunction { WARP_MALFUNCTION_COLOR } else { WARP_COLOR },
+            ),
+            _ => continue,
+        };
+
+        for ring in 0..3 {
+            let radius = radius * (1.0 - ring as f32 * 0.25);
+            gizmos.circle(Isometry3d::new(position, flat), radius.max(0.05), color);
+        }
+    }
+}
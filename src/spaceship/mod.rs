@@ -8,7 +8,9 @@ use crate::{
     sound::ambient::ThrusterSound,
     asset_loader::SceneAssets,
     collision_detection::{Collider, CollisionDamage},
+    config::GameConfig,
     health::Health,
+    players::{Player, PlayerCount},
     movement::{
         Acceleration,
         MovingObjectBundle,
@@ -17,62 +19,51 @@ use crate::{
         Velocity,
     },
     schedule::InGameSet,
-    sound::effects::ShootingSoundEvent,
-    state::GameState,
+    state::{GameSetup, GameState},
 };
 
+pub mod controls;
+pub mod hyperspace;
+pub mod projectiles;
 pub mod shield;
+pub mod upgrades;
+pub mod weapons;
+use controls::{read_ship_input, ShipInput};
+use hyperspace::{FlyableShip, HyperspaceController, HyperspacePlugin, HyperspaceRequestEvent};
+use projectiles::ProjectilePlugin;
 use shield::ShieldPlugin;
+use upgrades::{ShipStats, ShipUpgrades, UpgradesPlugin};
+use weapons::{Weapon, WeaponPlugin};
+pub use weapons::SpaceshipMissile;
 
 const SPACESHIP_STARTING_TRANSLATION: Vec3 = Vec3::new(0.0, 0.0, -20.0);
 const SPACESHIP_STARTING_VELOCITY: Vec3 = Vec3::new(0.0, 0.0, 1.0);
+const COOP_STARTING_OFFSET: Vec3 = Vec3::new(12.0, 0.0, 0.0);  // either side of the middle
 pub const SPACESHIP_RADIUS: f32 = 2.5;
 const SPACESHIP_SIZE: f32 = 0.8;
-const SPACESHIP_SPEED: f32 = 25.0;
-const SPACESHIP_ROTATION_SPEED: f32 = 2.5;
-const SPACESHIP_ROLL_SPEED: f32 = 2.5;
-const SPACESHIP_HEALTH: f32 = 100.0;
-const SPACESHIP_COLLISION_DAMAGE: f32 = 100.0;
-
-const SHIELD_COOLDOWN_SECS: f32 = 30.0;
-
-const MISSILE_FORWARD_SPAWN_SCALAR: f32 = 5.0;
-const MISSILE_RADIUS: f32 = 0.5;
-const MISSILE_SPEED: f32 = 50.0;
-const MISSILE_HEALTH: f32 = 1.0;
-const MISSILE_COLLISION_DAMAGE: f32 = 5.0;
-const MISSILE_RATE: f32 = 4.0;  // shots per second
-const MISSILE_MAX: usize = 3;  // maximum number of missiles allowed in the air
 
 
 #[derive(Component, Debug)]
 pub struct Spaceship;
 
-#[derive(Component, Debug)]
-pub struct SpaceshipMissile;
-
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ShieldState {
     Ready,
     Active,
-    Cooldown,
+    Recharging,
 }
 
 #[derive(Component, Debug)]
 pub struct ShieldController {
     pub state: ShieldState,
-    pub cooldown: Timer,
+    pub charge: f32,  // shield HP banked while the shield is down
 }
 
 
 // message for requesting a shield during gameplay
 #[derive(Message, Debug)]
-pub struct ShieldRequestEvent;
-
-
-#[derive(Resource, Debug)]
-pub struct MissileRateTimer {
-    timer: Timer,
+pub struct ShieldRequestEvent {
+    pub ship: Entity,
 }
 
 
@@ -80,45 +71,61 @@ pub struct SpaceshipPlugin;
 
 impl Plugin for SpaceshipPlugin {
     fn build(&self, app: &mut App) {
-        app.insert_resource(MissileRateTimer {
-            timer: Timer::from_seconds(
-                1.0 / MISSILE_RATE,
-                TimerMode::Once,
-            )
-        })
-        .add_plugins((
+        app.add_plugins((
             ShieldPlugin,
+            UpgradesPlugin,
+            WeaponPlugin,
+            ProjectilePlugin,
+            HyperspacePlugin,
         ))
         .add_message::<ShieldRequestEvent>()
-        .add_systems(PostStartup, spawn_spaceship)
-        .add_systems(OnEnter(GameState::GameOver), spawn_spaceship)
+        .add_systems(GameSetup, spawn_spaceships)
+        .add_systems(OnEnter(GameState::GameOver), spawn_spaceships)
+        .add_systems(Update,
+            change_player_count
+                .run_if(in_state(GameState::GameOver))
+                .run_if(resource_changed::<PlayerCount>),
+        )
         .add_systems(Update,
             (
-                spaceship_movement_controls,
-                spaceship_weapon_controls,
-                spaceship_shield_controls,
-                spaceship_thruster_sound_control,
+                read_ship_input,
+                (
+                    spaceship_movement_controls,
+                    spaceship_shield_controls,
+                    spaceship_hyperspace_controls,
+                    spaceship_thruster_sound_control,
+                ),
             )
+            .chain()
             .in_set(InGameSet::UserInput)
-        )
-        .add_systems(Update,
-            spaceship_destroyed.in_set(InGameSet::EntityUpdates))
-        .add_systems(Update,
-            despawn_missles.in_set(InGameSet::DespawnEntities),
         );
     }
 }
 
-fn spawn_spaceship(mut commands: Commands, scene_assets: Res<SceneAssets>) {
+// Each player's ship starts off on their own side of the screen.
+fn starting_translation(player: Player, player_count: PlayerCount) -> Vec3 {
+    if !player_count.is_coop() {
+        return SPACESHIP_STARTING_TRANSLATION;
+    }
+
+    let side = if player.0 == 0 { -1.0 } else { 1.0 };
+    SPACESHIP_STARTING_TRANSLATION + COOP_STARTING_OFFSET * side
+}
+
+pub fn spawn_spaceship(
+    commands: &mut Commands,
+    scene_assets: &SceneAssets,
+    config: &GameConfig,
+    player: Player,
+    player_count: PlayerCount,
+    upgrades: ShipUpgrades,
+) {
     let spaceship_xform = Transform::from_translation(
-        SPACESHIP_STARTING_TRANSLATION
+        starting_translation(player, player_count)
     ).with_scale(
         Vec3::ONE * SPACESHIP_SIZE
     );
 
-    let mut cooldown = Timer::from_seconds(SHIELD_COOLDOWN_SECS, TimerMode::Once);
-    cooldown.set_elapsed(cooldown.duration()); // mark finished
-
     commands.spawn((
         Name::new("spaceship"),
         MovingObjectBundle {
@@ -127,188 +134,132 @@ fn spawn_spaceship(mut commands: Commands, scene_assets: Res<SceneAssets>) {
             rotation: Rotation::new(0.0, 0.0, 0.0),
             collider: Collider::new(SPACESHIP_RADIUS),
             model: SceneBundle {
-                scene: SceneRoot(scene_assets.spaceship.clone()),
+                scene: SceneRoot(scene_assets.scene("spaceship")),
                 transform: spaceship_xform,
             }
         },
         Spaceship,
+        player,
+        ShipInput::default(),
         ShieldController {
             state: ShieldState::Ready,
-            cooldown,
+            charge: config.shield.hp,
         },
-        Health::new(SPACESHIP_HEALTH),
-        CollisionDamage::new(SPACESHIP_COLLISION_DAMAGE),
+        Health::new(config.spaceship.health),
+        CollisionDamage::new(config.spaceship.collision_damage),
+        ShipStats::from_upgrades(config, &upgrades),
+        upgrades,
+        Weapon::default(),
+        HyperspaceController::default(),
     ));
 }
 
-fn spaceship_movement_controls(
-    mut query: Query<(&mut Transform, &mut Acceleration), With<Spaceship>>,
-    keyboard_input: Res<ButtonInput<KeyCode>>,
-    time: Res<Time>,
+fn spawn_spaceships(
+    mut commands: Commands,
+    scene_assets: Res<SceneAssets>,
+    config: Res<GameConfig>,
+    player_count: Res<PlayerCount>,
 ) {
-    let Ok((mut transform, mut acceleration)) = query.single_mut() else {
-        return;
-    };
-
-    let mut rotation = 0.0;
-    let mut roll = 0.0;
-    let mut movement = 0.0;
-
-    // Forward or Backward
-    if keyboard_input.any_pressed([KeyCode::KeyS, KeyCode::ArrowDown]) {
-        movement = -SPACESHIP_SPEED;
-    }
-    else if keyboard_input.any_pressed([KeyCode::KeyW, KeyCode::ArrowUp]) {
-        movement = SPACESHIP_SPEED;
-    }
-
-    // Rotate left or right
-    if keyboard_input.any_pressed([KeyCode::KeyD, KeyCode::ArrowRight]) {
-        rotation = -SPACESHIP_ROTATION_SPEED * time.delta_secs();
-    }
-    else if keyboard_input.any_pressed([KeyCode::KeyA, KeyCode::ArrowLeft]) {
-        rotation = SPACESHIP_ROTATION_SPEED * time.delta_secs();
+    for player in player_count.players() {
+        spawn_spaceship(
+            &mut commands,
+            &scene_assets,
+            &config,
+            player,
+            *player_count,
+            ShipUpgrades::default(),
+        );
     }
+}
 
-    // Roll left or right
-    if keyboard_input.pressed(KeyCode::ShiftLeft) {
-        roll = -SPACESHIP_ROLL_SPEED * time.delta_secs();
-    }
-    else if keyboard_input.pressed(KeyCode::ControlLeft) {
-        roll = SPACESHIP_ROLL_SPEED * time.delta_secs();
+// Picking one or two players on the game over screen swaps the ships
+// over straight away, so you can see who is where.
+fn change_player_count(
+    mut commands: Commands,
+    ship_q: Query<Entity, With<Spaceship>>,
+    scene_assets: Res<SceneAssets>,
+    config: Res<GameConfig>,
+    player_count: Res<PlayerCount>,
+) {
+    for entity in ship_q.iter() {
+        commands.entity(entity).despawn();
     }
 
-    // Rotate around the Y axis.
-    // Ignores the Z axis rotation applied below.
-    transform.rotate_y(rotation);
-
-    // Rotate around the local Z axis.
-    // The rotation is relative to the current rotation.
-    transform.rotate_local_z(roll);
-
-    // update the spaceship's velocity based on new direction
-    acceleration.value = -transform.forward() * movement;
+    spawn_spaceships(commands, scene_assets, config, player_count);
 }
 
+// The ship closest to a point, for anything that goes after the players.
+// Works with whatever else the query fetches alongside the Transform.
+pub fn nearest_ship<'a, D>(
+    position: Vec3,
+    ships: impl IntoIterator<Item = (&'a Transform, D)>,
+) -> Option<(&'a Transform, D)> {
+    ships.into_iter().min_by(|(a, _), (b, _)| {
+        a.translation.distance_squared(position).total_cmp(&b.translation.distance_squared(position))
+    })
+}
 
-fn spaceship_weapon_controls(
-    mut commands: Commands,
-    mut rate_timer: ResMut<MissileRateTimer>,
+fn spaceship_movement_controls(
+    mut query: Query<(&mut Transform, &mut Acceleration, &ShipStats, &ShipInput), FlyableShip>,
+    config: Res<GameConfig>,
     time: Res<Time>,
-    spaceship_query: Query<&Transform, With<Spaceship>>,
-    missile_query: Query<(), With<SpaceshipMissile>>,
-    keyboard_input: Res<ButtonInput<KeyCode>>,
-    mut sound_event_writer: MessageWriter<ShootingSoundEvent>,
-    scene_assets: Res<SceneAssets>,
 ) {
-    rate_timer.timer.tick(time.delta());
+    for (mut transform, mut acceleration, stats, input) in query.iter_mut() {
+        // Forward or Backward
+        let movement = stats.thrust * input.thrust;
 
-    let Ok(spaceship_xform) = spaceship_query.single() else {
-        return;
-    };
+        // Rotate left or right
+        let rotation = config.spaceship.rotation_speed * time.delta_secs() * input.turn;
 
-    if keyboard_input.pressed(KeyCode::Space) &&
-       rate_timer.timer.is_finished()
-    {
-        let missile_number = missile_query.iter().len();
-
-        if missile_number < MISSILE_MAX {
-            rate_timer.timer.reset();
-
-            let mut missile_xform = Transform::from_translation(
-                spaceship_xform.translation + -spaceship_xform.forward() * MISSILE_FORWARD_SPAWN_SCALAR,
-            );
-            missile_xform.rotate(spaceship_xform.rotation);
-
-            commands.spawn((
-                Name::new("spaceship_missile"),
-                MovingObjectBundle {
-                    velocity: Velocity::new(-spaceship_xform.forward() * MISSILE_SPEED),
-                    acceleration: Acceleration::new(Vec3::ZERO),
-                    rotation: Rotation::new(0.0, 0.0, 0.0),
-                    collider: Collider::new(MISSILE_RADIUS),
-                    model: SceneBundle {
-                        scene: SceneRoot(scene_assets.missiles.clone()),
-                        transform: missile_xform,
-                    },
-                },
-                SpaceshipMissile,
-                Health::new(MISSILE_HEALTH),
-                CollisionDamage::new(MISSILE_COLLISION_DAMAGE),
-            ));
-
-            sound_event_writer.write(ShootingSoundEvent);
-        }
+        // Roll left or right
+        let roll = config.spaceship.roll_speed * time.delta_secs() * input.roll;
+
+        // Rotate around the Y axis.
+        // Ignores the Z axis rotation applied below.
+        transform.rotate_y(rotation);
+
+        // Rotate around the local Z axis.
+        // The rotation is relative to the current rotation.
+        transform.rotate_local_z(roll);
+
+        // update the spaceship's velocity based on new direction
+        acceleration.value = -transform.forward() * movement;
     }
 }
 
 
 fn spaceship_shield_controls(
-    keyboard_input: Res<ButtonInput<KeyCode>>,
+    query: Query<(Entity, &ShipInput), FlyableShip>,
     mut shield_request_writer: MessageWriter<ShieldRequestEvent>,
 ) {
-    if keyboard_input.just_pressed(KeyCode::Tab) {
-        // commands.entity(spaceship).insert(ShieldRequest);
-        shield_request_writer.write(ShieldRequestEvent);
-    }
-}
-
-fn despawn_missles(
-    mut commands: Commands,
-    camera_query: Query<&Projection, With<Camera>>,
-    missile_query: Query<(Entity, &Transform), With<SpaceshipMissile>>,
-) {
-    // When a missile goes off-screen, we despawn it.
-    let mut min_x: f32 = 0.0;
-    let mut max_x: f32 = 0.0;
-    let mut min_z: f32 = 0.0;
-    let mut max_z: f32 = 0.0;
-
-    let projection = camera_query.single().unwrap();
-    let bounds = projection.get_frustum_corners(0.0, 80.0);
-    for b in bounds {
-        if min_x > b.x {min_x = b.x}
-        if max_x < b.x {max_x = b.x}
-        if min_z > b.y {min_z = b.y}
-        if max_z < b.y {max_z = b.y}
-    }
-    
-    for (entity, transform) in missile_query.iter() {
-        if transform.translation.x < min_x ||
-           transform.translation.x > max_x ||
-           transform.translation.z < min_z ||
-           transform.translation.z > max_z
-        {
-            //println!("despawn_missiles(): Despawn entity: {:?}", entity);
-            commands.entity(entity).despawn();
+    for (ship, input) in query.iter() {
+        if input.shield {
+            shield_request_writer.write(ShieldRequestEvent { ship });
         }
     }
-
 }
 
-fn spaceship_destroyed(
-    mut next_state: ResMut<NextState<GameState>>,
-    query: Query<(), With<Spaceship>>,
+fn spaceship_hyperspace_controls(
+    query: Query<(Entity, &ShipInput), With<Spaceship>>,
+    mut hyperspace_request_writer: MessageWriter<HyperspaceRequestEvent>,
 ) {
-    if query.single().is_err() {
-        next_state.set(GameState::GameOver);
+    for (ship, input) in query.iter() {
+        if input.hyperspace {
+            hyperspace_request_writer.write(HyperspaceRequestEvent { ship });
+        }
     }
 }
 
+// There is only the one thruster sound, so it plays if anyone is thrusting.
 fn spaceship_thruster_sound_control(
-    keyboard_input: Res<ButtonInput<KeyCode>>,
+    query: Query<&ShipInput, FlyableShip>,
     mut thruster_audio: Query<&mut AudioSink, With<ThrusterSound>>,
 ) {
     let Ok(mut sink) = thruster_audio.single_mut() else {
         return;
     };
 
-    if keyboard_input.any_pressed([
-        KeyCode::ArrowUp,
-        KeyCode::ArrowDown,
-        KeyCode::KeyW,
-        KeyCode::KeyS,
-    ]) {
+    if query.iter().any(|input| input.thrust != 0.0) {
         sink.set_volume(Volume::Linear(1.0));
     } else {
         sink.set_volume(Volume::Linear(0.0));
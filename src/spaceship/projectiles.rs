@@ -0,0 +1,124 @@
+use bevy::prelude::*;
+
+use crate::{
+    collision_detection::{handle_collision_event, Collider},
+    health::Health,
+    movement::{play_area_bounds, Velocity},
+    schedule::InGameSet,
+};
+use super::{
+    shield::Shield,
+    Spaceship,
+    SpaceshipMissile,
+};
+
+// Projectile modifiers.  Each one is a component on the projectile, so a
+// weapon can hand out any combination of them: a piercing missile that
+// also ricochets goes through a few rocks and then bounces off the edge of
+// the screen to go through a few more.
+
+// Passes through this many targets before it stops at the next one.  Each
+// target is only damaged once, however long the projectile takes to get
+// through it.
+#[derive(Component, Debug)]
+pub struct Piercing {
+    pub remaining: u32,
+    pub hit: Vec<Entity>,
+}
+
+impl Piercing {
+    pub fn new(targets: u32) -> Self {
+        Self {
+            remaining: targets,
+            hit: vec![],
+        }
+    }
+}
+
+// Bounces off the edges of the screen this many times before it is allowed
+// to fly off.
+#[derive(Component, Debug)]
+pub struct Ricochet {
+    pub bounces: u32,
+}
+
+// Wraps around to the other side of the screen like the asteroids do, so it
+// needs a Lifetime as well or it would be around forever.
+#[derive(Component, Debug)]
+pub struct WrapAround;
+
+
+pub struct ProjectilePlugin;
+
+impl Plugin for ProjectilePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, (
+                register_piercing_hits.after(handle_collision_event),
+                ricochet_off_edges,
+            ).in_set(InGameSet::EntityUpdates),
+        );
+    }
+}
+
+// Anything the ship's missiles can do damage to.
+pub type MissileTarget = (With<Health>, Without<SpaceshipMissile>, Without<Spaceship>, Without<Shield>);
+
+// This runs after the collision events have been handled, so that a target
+// doesn't get added to the list before it has taken its damage.
+fn register_piercing_hits(
+    mut projectiles: Query<(&mut Piercing, &Collider)>,
+    targets: Query<(), MissileTarget>,
+) {
+    for (mut piercing, collider) in projectiles.iter_mut() {
+        for &entity in collider.colliding_entities.iter() {
+            if targets.get(entity).is_err() || piercing.hit.contains(&entity) {
+                continue;
+            }
+
+            piercing.hit.push(entity);
+            piercing.remaining = piercing.remaining.saturating_sub(1);
+        }
+    }
+}
+
+fn ricochet_off_edges(
+    camera_query: Query<&Projection, With<Camera>>,
+    mut query: Query<(&mut Ricochet, &mut Transform, &mut Velocity)>,
+) {
+    let Ok(projection) = camera_query.single() else {
+        return;
+    };
+    let (min, max) = play_area_bounds(projection);
+
+    for (mut ricochet, mut xform, mut velocity) in query.iter_mut() {
+        if ricochet.bounces == 0 {
+            continue;  // let it fly off the screen
+        }
+
+        let position = xform.translation;
+        let mut bounced = false;
+
+        // Only bounce if it is heading out, or it could get stuck flipping
+        // back and forth on the edge.
+        if (position.x < min.x && velocity.value.x < 0.0) ||
+           (position.x > max.x && velocity.value.x > 0.0)
+        {
+            velocity.value.x = -velocity.value.x;
+            bounced = true;
+        }
+
+        if (position.z < min.y && velocity.value.z < 0.0) ||
+           (position.z > max.y && velocity.value.z > 0.0)
+        {
+            velocity.value.z = -velocity.value.z;
+            bounced = true;
+        }
+
+        if bounced {
+            ricochet.bounces -= 1;
+            xform.translation.x = position.x.clamp(min.x, max.x);
+            xform.translation.z = position.z.clamp(min.y, max.y);
+            xform.rotation = Quat::from_rotation_arc(Vec3::Z, velocity.value.normalize_or(Vec3::Z));
+        }
+    }
+}
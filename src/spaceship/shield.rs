@@ -1,3 +1,5 @@
+use std::f32::consts::FRAC_PI_2;
+
 use bevy::prelude::*;
 use bevy::pbr::{MeshMaterial3d, StandardMaterial};
 use bevy::render::alpha::AlphaMode;
@@ -5,11 +7,19 @@ use bevy::platform::collections::HashMap;
 
 use crate::{
     asset_loader::SceneAssets,
-    collision_detection::Collider,
+    asteroids::Asteroid,
+    collision_detection::{handle_collision_event, Collider},
+    config::GameConfig,
+    difficulty::Difficulty,
     health::Health,
+    lifetime::Lifetime,
+    movement::Velocity,
+    pickups::ShieldOvercharge,
+    saucer::SaucerMissile,
     schedule::InGameSet,
 };
 use super::{
+    upgrades::ShipStats,
     ShieldController,
     ShieldRequestEvent,
     ShieldState,
@@ -21,6 +31,7 @@ use super::{
 #[derive(Component, Debug)]
 pub struct Shield {
     pub ship: Entity,
+    pub max_hp: f32,
 }
 
 #[derive(Component, Debug, Default)]
@@ -38,13 +49,34 @@ pub struct ShieldReadyEvent {
     pub ship: Entity,
 }
 
+// Sent by the collision handler whenever the shield takes damage.
+#[derive(Message, Debug)]
+pub struct ShieldHitEvent {
+    pub shield: Entity,
+    pub hitter: Entity,
+}
+
+// A ring rippling out from where something hit the shield.  The direction
+// is kept relative to the shield, so the ripple moves along with the ship.
+#[derive(Component, Debug)]
+pub struct ShieldRipple {
+    pub shield: Entity,
+    pub direction: Vec3,
+}
+
 const SHIELD_RADIUS: f32 = SPACESHIP_RADIUS * 2.0;
 const SHIELD_VISUAL_SCALE: f32 = SHIELD_RADIUS; // because model diameter is 2.0
-const SHIELD_HP: f32 = 60.0;
 const SHIELD_HIT_COOLDOWN_SECS: f32 = 0.40;
-const SHIELD_DECAY: f32 = 4.0;  // HP per second.
+// The shield can't be raised until it has at least this much charge.
+const SHIELD_MIN_CHARGE_FRACTION: f32 = 0.25;
 const SHIELD_BASE_ALPHA: f32 = 0.35; // tune: 0.25–0.45 feels good
 const SHIELD_MIN_ALPHA: f32 = 0.03;  // don’t go fully invisible until dead
+const SHIELD_FLASH_ALPHA: f32 = 0.5;  // extra alpha right after a hit
+const SHIELD_FLASH_EMISSIVE: f32 = 3.0;
+
+const SHIELD_RIPPLE_SECS: f32 = 0.35;
+const SHIELD_RIPPLE_RADIUS: f32 = SPACESHIP_RADIUS;
+const SHIELD_RIPPLE_COLOR: Color = Color::srgb(0.5, 0.9, 1.0);
 
 pub struct ShieldPlugin;
 
@@ -55,28 +87,40 @@ impl Plugin for ShieldPlugin {
             (
                 consume_shield_request,
                 shield_follow_ship,
-                shield_death_starts_cooldown,
-                tick_shield_cooldown,
+                shield_death_starts_recharge,
+                recharge_shield,
                 tick_shield_hit_cooldowns,
                 shield_cache_materials,
                 shield_apply_alpha_from_health,
                 shield_decay_health,
+                reflect_saucer_missiles,
             )
             .in_set(InGameSet::UserInput)
-        );        
+        )
+        .add_message::<ShieldHitEvent>()
+        .add_systems(Update,
+            (
+                shield_knockback,
+                spawn_shield_ripples.after(handle_collision_event),
+                draw_shield_ripples,
+            )
+            .chain()
+            .in_set(InGameSet::EntityUpdates)
+        );
     }
 }
 
 
-fn consume_shield_request(
+pub fn consume_shield_request(
     mut shield_request_reader: MessageReader<ShieldRequestEvent>,
     mut commands: Commands,
     scene_assets: Res<SceneAssets>,
-    mut ship_q: Query<(Entity, &mut ShieldController, &GlobalTransform), With<Spaceship>>,
+    mut ship_q: Query<(Entity, &mut ShieldController, &GlobalTransform, &ShipStats), With<Spaceship>>,
+    shield_q: Query<(Entity, &Shield, &Health)>,
 ) {
-    for _ in shield_request_reader.read() {
+    for &ShieldRequestEvent { ship } in shield_request_reader.read() {
         // spawn our shield if not already present
-        let Ok((ship_entity, mut controller, ship_gt)) = ship_q.single_mut() else { return; };
+        let Ok((ship_entity, mut controller, ship_gt, stats)) = ship_q.get_mut(ship) else { continue; };
 
         match controller.state {
             ShieldState::Ready => {
@@ -88,16 +132,18 @@ fn consume_shield_request(
                 let shield_xform = Transform::from_translation(ship_gt.translation());
                 let shield_gt = GlobalTransform::from(shield_xform);
 
+                // The shield only has as much HP as we have charged up.
                 commands.spawn((
                     Name::new("shield"),
-                    Shield { ship: ship_entity },
+                    Shield { ship: ship_entity, max_hp: stats.shield_hp },
                     ShieldHitCooldown { timer: hit_cd },
-                    Health::new(SHIELD_HP),
+                    Health::new(controller.charge),
                     Collider::new(SHIELD_RADIUS),
-                    SceneRoot(scene_assets.shield.clone()),
+                    SceneRoot(scene_assets.scene("shield")),
                     shield_xform,
                     shield_gt,
                 ));
+                controller.charge = 0.0;
 
                 #[cfg(debug_assertions)]
                 info!(
@@ -106,36 +152,58 @@ fn consume_shield_request(
                 );
             }
             ShieldState::Active => {
+                // Drop the shield, and bank whatever HP it had left.
+                for (shield_entity, shield, health) in shield_q.iter() {
+                    if shield.ship != ship_entity {
+                        continue;
+                    }
+
+                    controller.charge = health.value.max(0.0);
+                    commands.entity(shield_entity).despawn();
+                }
+
+                controller.state = if controller.charge >= min_shield_charge(stats) {
+                    ShieldState::Ready
+                }
+                else {
+                    ShieldState::Recharging
+                };
+
                 #[cfg(debug_assertions)]
-                info!("Shield requested while Active (toggle TBD)");
+                info!(
+                    "Shield dropped with {:.1} HP banked: Active -> {:?}",
+                    controller.charge,
+                    controller.state,
+                );
             }
-            ShieldState::Cooldown => {
+            ShieldState::Recharging => {
                 #[cfg(debug_assertions)]
-                info!("Shield requested during Cooldown (ignored)");
+                info!("Shield requested while Recharging (ignored)");
             }
         }
     }
 }
 
 fn shield_follow_ship(
-    ship_q: Query<(Entity, &GlobalTransform), With<Spaceship>>,
-    mut shield_q: Query<(&Shield, &mut Transform)>,
+    mut commands: Commands,
+    ship_q: Query<&GlobalTransform, With<Spaceship>>,
+    mut shield_q: Query<(Entity, &Shield, &mut Transform)>,
 ) {
-    let Ok((ship_e, ship_gt)) = ship_q.single() else { return; };
-
-    // Convert GlobalTransform to a local Transform we can apply to the shield.
-    let ship_tf = ship_gt.compute_transform();
-    
-    for (shield, mut shield_tf) in shield_q.iter_mut() {
-        if shield.ship != ship_e {
+    for (shield_entity, shield, mut shield_tf) in shield_q.iter_mut() {
+        // In co-op the game goes on after a ship is lost, so its shield
+        // has to go with it.
+        let Ok(ship_gt) = ship_q.get(shield.ship) else {
+            commands.entity(shield_entity).try_despawn();
             continue;
-        }
-        *shield_tf = ship_tf;
+        };
+
+        // Convert GlobalTransform to a local Transform we can apply to the shield.
+        *shield_tf = ship_gt.compute_transform();
         shield_tf.scale = Vec3::ONE * SHIELD_VISUAL_SCALE;
     }
 }
 
-fn shield_death_starts_cooldown(
+fn shield_death_starts_recharge(
     mut commands: Commands,
     shield_q: Query<(Entity, &Health, &Shield)>,
     mut ship_q: Query<&mut ShieldController, With<Spaceship>>,
@@ -148,44 +216,54 @@ fn shield_death_starts_cooldown(
             commands.entity(shield_entity).despawn();
 
             if let Ok(mut controller) = ship_q.get_mut(shield.ship) {
-                controller.state = ShieldState::Cooldown;
-                controller.cooldown.reset();
+                controller.state = ShieldState::Recharging;
+                controller.charge = 0.0;
 
                 #[cfg(debug_assertions)]
-                info!("Shield died: Active -> Cooldown");
+                info!("Shield died: Active -> Recharging");
             }
         }
     }
 }
 
 
-fn tick_shield_cooldown(
+// While the shield is down it charges back up a little at a time.  It takes
+// the ship's shield cooldown to go from empty to full, but it can be raised
+// again long before that.
+fn recharge_shield(
     time: Res<Time>,
-    mut q: Query<(Entity, &mut ShieldController), With<Spaceship>>,
+    mut q: Query<(Entity, &mut ShieldController, &ShipStats), With<Spaceship>>,
     mut shield_ready_writer: MessageWriter<ShieldReadyEvent>,
 ) {
-    let Ok((ship_e, mut controller)) = q.single_mut() else { return; };
-
-    if controller.state != ShieldState::Cooldown {
-        return;
-    }
+    for (ship_e, mut controller, stats) in q.iter_mut() {
+        if controller.state == ShieldState::Active {
+            continue;
+        }
 
-    controller.cooldown.tick(time.delta());
+        let rate = stats.shield_hp / stats.shield_cooldown_secs;
+        controller.charge = (controller.charge + rate * time.delta_secs()).min(stats.shield_hp);
 
-    if controller.cooldown.just_finished() {
-        controller.state = ShieldState::Ready;
+        if controller.state == ShieldState::Recharging &&
+           controller.charge >= min_shield_charge(stats)
+        {
+            controller.state = ShieldState::Ready;
 
-        #[cfg(debug_assertions)]
-        info!(
-            "Shield cooldown complete: Cooldown -> Ready (ship={:?}, elapsed={:?})",
-            ship_e,
-            controller.cooldown.elapsed()
-        );
+            #[cfg(debug_assertions)]
+            info!(
+                "Shield recharged: Recharging -> Ready (ship={:?}, charge={:.1})",
+                ship_e,
+                controller.charge,
+            );
 
-        shield_ready_writer.write(ShieldReadyEvent { ship: ship_e });
+            shield_ready_writer.write(ShieldReadyEvent { ship: ship_e });
+        }
     }
 }
 
+pub fn min_shield_charge(stats: &ShipStats) -> f32 {
+    stats.shield_hp * SHIELD_MIN_CHARGE_FRACTION
+}
+
 
 fn tick_shield_hit_cooldowns(
     time: Res<Time>,
@@ -253,16 +331,22 @@ fn shield_cache_materials(
 
 fn shield_apply_alpha_from_health(
     mut materials: ResMut<Assets<StandardMaterial>>,
-    q: Query<(&Health, &ShieldMaterialCache), With<Shield>>,
+    q: Query<(&Health, &ShieldMaterialCache, &Shield, &ShieldHitCooldown)>,
 ) {
-    for (health, cache) in q.iter() {
-        let t = (health.value / SHIELD_HP).clamp(0.0, 1.0);
-        let alpha = (SHIELD_MIN_ALPHA + (SHIELD_BASE_ALPHA - SHIELD_MIN_ALPHA) * t).clamp(0.0, 1.0);
+    for (health, cache, shield, hit_cd) in q.iter() {
+        // The hit cooldown restarts every time the shield takes damage, so
+        // it doubles as the timer for the impact flash.
+        let flash = 1.0 - hit_cd.timer.fraction();
+
+        let t = (health.value / shield.max_hp).clamp(0.0, 1.0);
+        let alpha = (SHIELD_MIN_ALPHA + (SHIELD_BASE_ALPHA - SHIELD_MIN_ALPHA) * t +
+                     SHIELD_FLASH_ALPHA * flash).clamp(0.0, 1.0);
 
         for h in cache.handles.iter() {
             if let Some(mat) = materials.get_mut(h.id()) {
                 let base = mat.base_color.to_srgba();
                 mat.base_color = Color::srgba(base.red, base.green, base.blue, alpha);
+                mat.emissive = mat.base_color.to_linear() * SHIELD_FLASH_EMISSIVE * flash;
                 // keep blend (some materials may get overwritten by glTF defaults)
                 mat.alpha_mode = AlphaMode::Blend;
             }
@@ -271,16 +355,164 @@ fn shield_apply_alpha_from_health(
 }
 
 fn shield_decay_health(
-    mut shield_q: Query<&mut Health, With<Shield>>,
+    mut shield_q: Query<(&Shield, &mut Health)>,
+    overcharged_q: Query<(), With<ShieldOvercharge>>,
+    difficulty: Res<Difficulty>,
     time: Res<Time>,
 ) {
     // In the interest of fairness, we would like the shield to not be
     // alive forever even if it doesn't get hit.  So we will make its health
     // decay over time.  We would like the shield to last about 10-15 seconds.
-    for mut health in shield_q.iter_mut() {
-        let decay_value = SHIELD_DECAY * time.delta_secs();
+    for (shield, mut health) in shield_q.iter_mut() {
+        // ... unless it is overcharged.
+        if overcharged_q.contains(shield.ship) {
+            continue;
+        }
+
+        let decay_value = difficulty.settings().shield_decay * time.delta_secs();
         if health.value > 0.0 {
             health.value -= health.value.min(decay_value);
         }
     }
 }
+
+type ReflectableMissiles<'w, 's> = Query<
+    'w,
+    's,
+    (&'static mut Transform, &'static mut Velocity, &'static mut Collider),
+    (With<SaucerMissile>, Without<Shield>),
+>;
+
+// An overcharged shield bounces saucer missiles back instead of taking the
+// hit.  The collisions were found last frame but haven't been handled yet,
+// so we take them back out of both colliders before anyone sees them.
+fn reflect_saucer_missiles(
+    mut shield_q: Query<(Entity, &Shield, &Transform, &mut Collider)>,
+    mut missile_q: ReflectableMissiles,
+    overcharged_q: Query<(), With<ShieldOvercharge>>,
+) {
+    for (shield_e, shield, shield_xform, mut shield_collider) in shield_q.iter_mut() {
+        if !overcharged_q.contains(shield.ship) {
+            continue;
+        }
+
+        let mut reflected = vec![];
+
+        for &entity in shield_collider.colliding_entities.iter() {
+            let Ok((mut xform, mut velocity, mut collider)) = missile_q.get_mut(entity) else {
+                continue;
+            };
+
+            let normal = (xform.translation - shield_xform.translation)
+                .with_y(0.0)
+                .normalize_or(-velocity.value.normalize_or(Vec3::Z));
+
+            // Only bounce it if it is still heading in.
+            if velocity.value.dot(normal) < 0.0 {
+                velocity.value = velocity.value.reflect(normal);
+            }
+
+            xform.translation = shield_xform.translation + normal * (SHIELD_RADIUS + collider.radius);
+            let direction = velocity.value.normalize_or(normal);
+            xform.look_to(direction, Vec3::Z);
+
+            collider.colliding_entities.retain(|e| *e != shield_e);
+            reflected.push(entity);
+
+            #[cfg(debug_assertions)]
+            info!("Overcharged shield reflected saucer missile {:?}", entity);
+        }
+
+        shield_collider.colliding_entities.retain(|e| !reflected.contains(e));
+    }
+}
+
+type KnockbackAsteroids<'w, 's> = Query<
+    'w,
+    's,
+    (&'static mut Transform, &'static mut Velocity, &'static Collider),
+    (With<Asteroid>, Without<Shield>),
+>;
+
+// Push asteroids back out of the shield, bouncing them off it based on how
+// fast they are coming in relative to the ship.  The ship gets shoved back a
+// little too, but it is a lot heavier.
+fn shield_knockback(
+    shield_q: Query<(&Shield, &Transform, &Collider)>,
+    mut asteroid_q: KnockbackAsteroids,
+    mut ship_q: Query<&mut Velocity, (With<Spaceship>, Without<Asteroid>)>,
+    config: Res<GameConfig>,
+) {
+    for (shield, shield_xform, shield_collider) in shield_q.iter() {
+        let Ok(mut ship_velocity) = ship_q.get_mut(shield.ship) else {
+            continue;
+        };
+
+        for &entity in shield_collider.colliding_entities.iter() {
+            let Ok((mut xform, mut velocity, collider)) = asteroid_q.get_mut(entity) else {
+                continue;
+            };
+
+            let normal = (xform.translation - shield_xform.translation)
+                .with_y(0.0)
+                .normalize_or(Vec3::X);
+
+            // Only bounce it if it is still heading in, or it could get
+            // stuck bouncing back and forth on the edge of the shield.
+            let closing_speed = (velocity.value - ship_velocity.value).dot(normal);
+            if closing_speed < 0.0 {
+                velocity.value -= normal * closing_speed * (1.0 + config.shield.restitution);
+                ship_velocity.value += normal * closing_speed * config.shield.recoil;
+            }
+
+            xform.translation = shield_xform.translation + normal * (SHIELD_RADIUS + collider.radius);
+        }
+    }
+}
+
+fn spawn_shield_ripples(
+    mut commands: Commands,
+    mut shield_hit_reader: MessageReader<ShieldHitEvent>,
+    shield_q: Query<&Transform, With<Shield>>,
+    hitter_q: Query<&Transform>,
+) {
+    for event in shield_hit_reader.read() {
+        let (Ok(shield_xform), Ok(hitter_xform)) = (shield_q.get(event.shield), hitter_q.get(event.hitter)) else {
+            continue;
+        };
+
+        let direction = (hitter_xform.translation - shield_xform.translation)
+            .with_y(0.0)
+            .normalize_or(Vec3::Z);
+
+        commands.spawn((
+            Name::new("shield_ripple"),
+            ShieldRipple {
+                shield: event.shield,
+                direction,
+            },
+            Lifetime::new(SHIELD_RIPPLE_SECS),
+        ));
+    }
+}
+
+fn draw_shield_ripples(
+    mut gizmos: Gizmos,
+    ripple_q: Query<(&ShieldRipple, &Lifetime)>,
+    shield_q: Query<&Transform, With<Shield>>,
+) {
+    let flat = Quat::from_rotation_x(FRAC_PI_2);
+
+    for (ripple, lifetime) in ripple_q.iter() {
+        let Ok(shield_xform) = shield_q.get(ripple.shield) else {
+            continue;
+        };
+
+        let t = lifetime.timer.fraction();
+        let center = shield_xform.translation + ripple.direction * SHIELD_RADIUS;
+        let color = SHIELD_RIPPLE_COLOR.with_alpha(1.0 - t);
+
+        gizmos.circle(Isometry3d::new(center, flat), (SHIELD_RIPPLE_RADIUS * t).max(0.05), color);
+        gizmos.circle(Isometry3d::new(center, flat), (SHIELD_RIPPLE_RADIUS * t * 0.5).max(0.05), color);
+    }
+}
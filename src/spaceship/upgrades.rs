@@ -0,0 +1,164 @@
+use bevy::prelude::*;
+
+use crate::{config::GameConfig, health::Health};
+use super::Spaceship;
+
+pub const UPGRADE_MAX_LEVEL: u32 = 5;
+const SHIELD_COOLDOWN_MIN_SECS: f32 = 8.0;
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UpgradeKind {
+    MissileRate,
+    MissileMax,
+    MissileSpeed,
+    Hull,
+    ShieldHp,
+    ShieldCooldown,
+    Thrust,
+}
+
+impl UpgradeKind {
+    // The order they are listed in the shop.
+    pub const ALL: [UpgradeKind; 7] = [
+        UpgradeKind::MissileRate,
+        UpgradeKind::MissileMax,
+        UpgradeKind::MissileSpeed,
+        UpgradeKind::Hull,
+        UpgradeKind::ShieldHp,
+        UpgradeKind::ShieldCooldown,
+        UpgradeKind::Thrust,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            UpgradeKind::MissileRate => "Fire rate",
+            UpgradeKind::MissileMax => "Missile capacity",
+            UpgradeKind::MissileSpeed => "Missile speed",
+            UpgradeKind::Hull => "Hull",
+            UpgradeKind::ShieldHp => "Shield strength",
+            UpgradeKind::ShieldCooldown => "Shield recharge",
+            UpgradeKind::Thrust => "Thrust",
+        }
+    }
+
+    // Ore credits for the first level.  Every level after that costs as much
+    // again, so the third level costs three times this.
+    fn base_cost(&self) -> i32 {
+        match self {
+            UpgradeKind::MissileRate => 15,
+            UpgradeKind::MissileMax => 20,
+            UpgradeKind::MissileSpeed => 10,
+            UpgradeKind::Hull => 20,
+            UpgradeKind::ShieldHp => 15,
+            UpgradeKind::ShieldCooldown => 15,
+            UpgradeKind::Thrust => 10,
+        }
+    }
+
+    fn index(&self) -> usize {
+        match self {
+            UpgradeKind::MissileRate => 0,
+            UpgradeKind::MissileMax => 1,
+            UpgradeKind::MissileSpeed => 2,
+            UpgradeKind::Hull => 3,
+            UpgradeKind::ShieldHp => 4,
+            UpgradeKind::ShieldCooldown => 5,
+            UpgradeKind::Thrust => 6,
+        }
+    }
+}
+
+// How far each of the ship's systems has been upgraded.  This lives on the
+// spaceship, so a new ship (i.e. a new game) starts over from scratch.
+#[derive(Component, Debug, Default, Clone)]
+pub struct ShipUpgrades {
+    levels: [u32; 7],
+}
+
+impl ShipUpgrades {
+    pub fn level(&self, kind: UpgradeKind) -> u32 {
+        self.levels[kind.index()]
+    }
+
+    // None once it is maxed out.
+    pub fn cost(&self, kind: UpgradeKind) -> Option<i32> {
+        let level = self.level(kind);
+        (level < UPGRADE_MAX_LEVEL).then(|| kind.base_cost() * (level as i32 + 1))
+    }
+
+    pub fn upgrade(&mut self, kind: UpgradeKind) {
+        let level = &mut self.levels[kind.index()];
+        *level = (*level + 1).min(UPGRADE_MAX_LEVEL);
+    }
+}
+
+// The ship's tunables, worked out from the base values in the GameConfig
+// and the upgrades.  Systems (and the HUD) read these instead of the config.
+#[derive(Component, Debug, Clone, PartialEq)]
+pub struct ShipStats {
+    pub missile_rate: f32,  // shots per second
+    pub missile_max: usize,
+    pub missile_speed: f32,
+    pub max_health: f32,
+    pub shield_hp: f32,
+    pub shield_cooldown_secs: f32,
+    pub thrust: f32,
+}
+
+impl ShipStats {
+    pub fn from_upgrades(config: &GameConfig, upgrades: &ShipUpgrades) -> Self {
+        let level = |kind| upgrades.level(kind) as f32;
+        let ship = &config.spaceship;
+
+        Self {
+            missile_rate: ship.missile_rate + 0.75 * level(UpgradeKind::MissileRate),
+            missile_max: ship.missile_max + upgrades.level(UpgradeKind::MissileMax) as usize,
+            missile_speed: ship.missile_speed + 8.0 * level(UpgradeKind::MissileSpeed),
+            max_health: ship.health + 20.0 * level(UpgradeKind::Hull),
+            shield_hp: config.shield.hp + 15.0 * level(UpgradeKind::ShieldHp),
+            shield_cooldown_secs: (config.shield.cooldown_secs - 4.0 * level(UpgradeKind::ShieldCooldown))
+                .max(SHIELD_COOLDOWN_MIN_SECS),
+            thrust: ship.thrust + 4.0 * level(UpgradeKind::Thrust),
+        }
+    }
+}
+
+
+pub struct UpgradesPlugin;
+
+impl Plugin for UpgradesPlugin {
+    fn build(&self, app: &mut App) {
+        // Upgrades are bought while the game is paused in the shop, so this
+        // can't be part of the InGameSet.
+        app.add_systems(Update, apply_ship_upgrades);
+    }
+}
+
+// Also runs when the config is reloaded, so new base values take effect on
+// the ship that is already flying.
+fn apply_ship_upgrades(
+    mut query: Query<(Ref<ShipUpgrades>, &mut ShipStats, &mut Health), With<Spaceship>>,
+    config: Res<GameConfig>,
+) {
+    for (upgrades, mut stats, mut health) in query.iter_mut() {
+        if !upgrades.is_changed() && !config.is_changed() {
+            continue;
+        }
+
+        let new_stats = ShipStats::from_upgrades(&config, &upgrades);
+        if new_stats == *stats {
+            continue;
+        }
+
+        // A bigger hull comes with the extra hit points filled in.  A
+        // smaller one (from the config) can't hold more than it has room for.
+        health.value += (new_stats.max_health - stats.max_health).max(0.0);
+        health.value = health.value.min(new_stats.max_health);
+
+        #[cfg(debug_assertions)]
+        info!("Ship stats: {:?}", new_stats);
+
+        *stats = new_stats;
+    }
+}
@@ -0,0 +1,573 @@
+use bevy::prelude::*;
+
+use crate::{
+    asset_loader::SceneAssets,
+    asteroids::Asteroid,
+    collision_detection::{Collider, CollisionDamage},
+    config::GameConfig,
+    health::Health,
+    lifetime::{Lifetime, MaxRange},
+    movement::{
+        Acceleration,
+        MovingObjectBundle,
+        Rotation,
+        SceneBundle,
+        Velocity,
+    },
+    physics::{Mass, MISSILE_MASS},
+    pickups::{RapidFire, SpreadShot},
+    players::Player,
+    saucer::Saucer,
+    schedule::InGameSet,
+    sound::effects::ShootingSoundEvent,
+};
+use super::{
+    controls::{read_ship_input, ShipInput},
+    hyperspace::FlyableShip,
+    projectiles::{Piercing, Ricochet, WrapAround},
+    upgrades::ShipStats,
+};
+
+const MISSILE_FORWARD_SPAWN_SCALAR: f32 = 5.0;
+const MISSILE_HEALTH: f32 = 1.0;
+
+const RAPID_FIRE_MULTIPLIER: f32 = 2.0;  // applies to both rate & max missiles
+const SPREAD_SHOT_ANGLE: f32 = 0.26;  // radians between spread shot missiles
+
+const SPREAD_WEAPON_ANGLE: f32 = 0.18;  // radians between the five spread missiles
+const HEAT_COOL_RATE: f32 = 0.35;  // heat lost per second
+const HEAT_RECOVERED: f32 = 0.3;  // an overheated weapon works again below this
+
+// The beam charges while the trigger is held and fires on release.  The
+// longer the charge, the harder it hits.
+const BEAM_CHARGE_SECS: f32 = 1.2;
+const BEAM_MIN_CHARGE: f32 = 0.15;
+const BEAM_MAX_DAMAGE_SCALAR: f32 = 4.0;
+
+const HOMING_TURN_RATE: f32 = 3.0;  // radians per second
+const HOMING_RANGE: f32 = 40.0;
+
+const MINE_DROP_DISTANCE: f32 = 4.0;  // behind the ship
+const MINE_VELOCITY_SCALAR: f32 = 0.2;  // fraction of the ship's velocity
+const MINE_LIFETIME_SECS: f32 = 20.0;
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WeaponKind {
+    Blaster,
+    Spread,
+    Beam,
+    Homing,
+    Mines,
+}
+
+impl WeaponKind {
+    // The order the cycle key goes through them.
+    pub const ALL: [WeaponKind; 5] = [
+        WeaponKind::Blaster,
+        WeaponKind::Spread,
+        WeaponKind::Beam,
+        WeaponKind::Homing,
+        WeaponKind::Mines,
+    ];
+
+    pub fn props(&self) -> &'static WeaponProperties {
+        match self {
+            WeaponKind::Blaster => &BLASTER,
+            WeaponKind::Spread => &SPREAD,
+            WeaponKind::Beam => &BEAM,
+            WeaponKind::Homing => &HOMING,
+            WeaponKind::Mines => &MINES,
+        }
+    }
+
+    fn index(&self) -> usize {
+        match self {
+            WeaponKind::Blaster => 0,
+            WeaponKind::Spread => 1,
+            WeaponKind::Beam => 2,
+            WeaponKind::Homing => 3,
+            WeaponKind::Mines => 4,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct WeaponProperties {
+    pub name: &'static str,
+    pub projectile_name: &'static str,
+    pub fire_rate: f32,  // shots per second
+    pub projectile_speed: f32,
+    pub projectile_radius: f32,
+    pub projectile_scale: Vec3,
+    pub damage: f32,
+    pub scene: &'static str,  // asset manifest ids
+    pub sound: &'static str,
+    pub max_ammo: Option<u32>,  // None for unlimited
+    pub ammo_regen_secs: f32,  // time to get one round back
+    pub heat_per_shot: f32,  // the weapon overheats at 1.0
+    // Projectile modifiers, 0 for none.
+    pub pierce: u32,  // targets to pass through
+    pub ricochet: u32,  // bounces off the screen edges
+    pub wrap_secs: f32,  // wraps around the screen for this long
+    pub max_range: f32,  // how far it flies before it is gone
+}
+
+const BLASTER: WeaponProperties = WeaponProperties {
+    name: "Blaster",
+    projectile_name: "spaceship_missile",
+    // The blaster's rate and speed come from the spaceship config.
+    fire_rate: 0.0,
+    projectile_speed: 0.0,
+    projectile_radius: 0.5,
+    projectile_scale: Vec3::ONE,
+    damage: 5.0,
+    scene: "missiles",
+    sound: "shooting_sound",
+    max_ammo: None,
+    ammo_regen_secs: 0.0,
+    heat_per_shot: 0.0,
+    pierce: 0,
+    ricochet: 0,
+    wrap_secs: 0.0,
+    max_range: 75.0,
+};
+
+const SPREAD: WeaponProperties = WeaponProperties {
+    name: "Spread",
+    projectile_name: "spread_missile",
+    fire_rate: 2.5,
+    projectile_speed: 45.0,
+    projectile_radius: 0.5,
+    projectile_scale: Vec3::ONE,
+    damage: 4.0,
+    scene: "missiles",
+    sound: "spread_sound",
+    max_ammo: None,
+    ammo_regen_secs: 0.0,
+    heat_per_shot: 0.22,
+    pierce: 0,
+    ricochet: 1,
+    wrap_secs: 0.0,
+    max_range: 60.0,
+};
+
+const BEAM: WeaponProperties = WeaponProperties {
+    name: "Beam",
+    projectile_name: "beam",
+    fire_rate: 1.0,
+    projectile_speed: 90.0,
+    projectile_radius: 0.8,
+    projectile_scale: Vec3::new(1.5, 1.5, 5.0),
+    damage: 10.0,
+    scene: "beam",
+    sound: "beam_sound",
+    max_ammo: None,
+    ammo_regen_secs: 0.0,
+    heat_per_shot: 0.0,
+    pierce: 3,
+    ricochet: 0,
+    wrap_secs: 0.0,
+    max_range: 110.0,
+};
+
+const HOMING: WeaponProperties = WeaponProperties {
+    name: "Homing",
+    projectile_name: "homing_missile",
+    fire_rate: 2.0,
+    projectile_speed: 30.0,
+    projectile_radius: 0.6,
+    projectile_scale: Vec3::splat(1.3),
+    damage: 12.0,
+    scene: "homing_missile",
+    sound: "homing_sound",
+    max_ammo: Some(8),
+    ammo_regen_secs: 3.0,
+    heat_per_shot: 0.0,
+    pierce: 0,
+    ricochet: 0,
+    wrap_secs: 5.0,
+    max_range: 0.0,
+};
+
+const MINES: WeaponProperties = WeaponProperties {
+    name: "Mines",
+    projectile_name: "mine",
+    fire_rate: 1.5,
+    projectile_speed: 0.0,
+    projectile_radius: 1.2,
+    projectile_scale: Vec3::splat(2.5),
+    damage: 30.0,
+    scene: "mine",
+    sound: "mine_sound",
+    max_ammo: Some(5),
+    ammo_regen_secs: 6.0,
+    heat_per_shot: 0.0,
+    pierce: 0,
+    ricochet: 0,
+    wrap_secs: 0.0,
+    max_range: 0.0,
+};
+
+// Ammo and heat are kept per weapon, so switching away doesn't reset them.
+#[derive(Debug)]
+pub struct WeaponSlot {
+    pub ammo: Option<u32>,
+    pub ammo_timer: Timer,
+    pub heat: f32,
+    pub overheated: bool,
+}
+
+impl WeaponSlot {
+    fn new(kind: WeaponKind) -> Self {
+        let props = kind.props();
+        Self {
+            ammo: props.max_ammo,
+            ammo_timer: Timer::from_seconds(props.ammo_regen_secs.max(0.1), TimerMode::Repeating),
+            heat: 0.0,
+            overheated: false,
+        }
+    }
+}
+
+#[derive(Component, Debug)]
+pub struct Weapon {
+    pub selected: WeaponKind,
+    pub cooldown: Timer,
+    pub charge: f32,  // beam charge, 0.0 - 1.0
+    slots: [WeaponSlot; 5],
+}
+
+impl Default for Weapon {
+    fn default() -> Self {
+        // The cooldown gets its proper length from whichever weapon fires.
+        let mut cooldown = Timer::from_seconds(0.0, TimerMode::Once);
+        cooldown.set_elapsed(cooldown.duration());  // ready to fire
+
+        Self {
+            selected: WeaponKind::Blaster,
+            cooldown,
+            charge: 0.0,
+            slots: WeaponKind::ALL.map(WeaponSlot::new),
+        }
+    }
+}
+
+impl Weapon {
+    pub fn slot(&self, kind: WeaponKind) -> &WeaponSlot {
+        &self.slots[kind.index()]
+    }
+
+    fn slot_mut(&mut self, kind: WeaponKind) -> &mut WeaponSlot {
+        &mut self.slots[kind.index()]
+    }
+
+    fn select_next(&mut self) {
+        let next = (self.selected.index() + 1) % WeaponKind::ALL.len();
+        self.selected = WeaponKind::ALL[next];
+        self.charge = 0.0;
+    }
+}
+
+// Marks everything the spaceship fires, and which weapon it came from.  It
+// also gets the ship's Player, so the points go to whoever fired it.
+#[derive(Component, Debug)]
+pub struct SpaceshipMissile {
+    pub weapon: WeaponKind,
+}
+
+#[derive(Component, Debug)]
+pub struct Homing {
+    pub turn_rate: f32,
+}
+
+
+pub struct WeaponPlugin;
+
+impl Plugin for WeaponPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, (
+                cycle_weapon,
+                fire_weapon,
+            )
+            .chain()
+            .after(read_ship_input)
+            .in_set(InGameSet::UserInput),
+        )
+        .add_systems(Update, (
+                tick_weapons,
+                homing_guidance,
+            ).in_set(InGameSet::EntityUpdates),
+        );
+    }
+}
+
+fn cycle_weapon(
+    mut query: Query<(&mut Weapon, &ShipInput), FlyableShip>,
+) {
+    for (mut weapon, input) in query.iter_mut() {
+        if !input.cycle_weapon {
+            continue;
+        }
+
+        weapon.select_next();
+
+        #[cfg(debug_assertions)]
+        info!("Weapon selected: {}", weapon.selected.props().name);
+    }
+}
+
+// Everything about a ship that has a say in how (and whether) it fires.
+type ShipGuns = (
+    &'static Player,
+    &'static Transform,
+    &'static Velocity,
+    &'static ShipStats,
+    &'static ShipInput,
+    &'static mut Weapon,
+    Has<RapidFire>,
+    Has<SpreadShot>,
+);
+
+fn fire_weapon(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut spaceship_query: Query<ShipGuns, FlyableShip>,
+    missile_query: Query<(&SpaceshipMissile, &Player)>,
+    mut sound_event_writer: MessageWriter<ShootingSoundEvent>,
+    scene_assets: Res<SceneAssets>,
+    config: Res<GameConfig>,
+) {
+    let ship = &config.spaceship;
+
+    for (
+        player,
+        spaceship_xform,
+        spaceship_velocity,
+        stats,
+        input,
+        mut weapon,
+        rapid_fire,
+        spread_shot,
+    ) in spaceship_query.iter_mut() {
+        // Upgrades and rapid fire just make the clock run faster, for every
+        // weapon.  Rapid fire also lets more missiles be in the air at once.
+        let mut rate_scale = stats.missile_rate / ship.missile_rate;
+        let mut missile_max = stats.missile_max;
+        if rapid_fire {
+            rate_scale *= RAPID_FIRE_MULTIPLIER;
+            missile_max = (missile_max as f32 * RAPID_FIRE_MULTIPLIER) as usize;
+        }
+
+        weapon.cooldown.tick(time.delta().mul_f32(rate_scale));
+
+        let kind = weapon.selected;
+        let props = kind.props();
+        let trigger = input.fire;
+        let (fire_rate, projectile_speed) = match kind {
+            WeaponKind::Blaster => (ship.missile_rate, ship.missile_speed),
+            _ => (props.fire_rate, props.projectile_speed),
+        };
+
+        // The beam charges up while the trigger is held and goes off when it is
+        // let go.  Everything else fires while the trigger is held.
+        let damage_scale = if kind == WeaponKind::Beam {
+            if trigger {
+                if weapon.cooldown.is_finished() {
+                    weapon.charge = (weapon.charge + time.delta_secs() / BEAM_CHARGE_SECS).min(1.0);
+                }
+                continue;
+            }
+
+            let charge = std::mem::take(&mut weapon.charge);
+            if charge < BEAM_MIN_CHARGE {
+                continue;
+            }
+            1.0 + (BEAM_MAX_DAMAGE_SCALAR - 1.0) * charge
+        }
+        else {
+            if !trigger {
+                continue;
+            }
+            1.0
+        };
+
+        if !weapon.cooldown.is_finished() {
+            continue;
+        }
+
+        let slot = weapon.slot(kind);
+        if slot.overheated || slot.ammo == Some(0) {
+            continue;
+        }
+
+        if kind == WeaponKind::Blaster {
+            let missile_number = missile_query
+                .iter()
+                .filter(|(missile, owner)| missile.weapon == WeaponKind::Blaster && *owner == player)
+                .count();
+
+            if missile_number >= missile_max {
+                continue;
+            }
+        }
+
+        // A spread shot counts as one shot towards the maximum missile count.
+        let angles: &[f32] = match kind {
+            WeaponKind::Blaster if spread_shot => &[-SPREAD_SHOT_ANGLE, 0.0, SPREAD_SHOT_ANGLE],
+            WeaponKind::Spread => &[
+                -2.0 * SPREAD_WEAPON_ANGLE,
+                -SPREAD_WEAPON_ANGLE,
+                0.0,
+                SPREAD_WEAPON_ANGLE,
+                2.0 * SPREAD_WEAPON_ANGLE,
+            ],
+            _ => &[0.0],
+        };
+
+        let speed = projectile_speed * stats.missile_speed / ship.missile_speed;
+
+        for angle in angles {
+            let rotation = Quat::from_rotation_y(*angle) * spaceship_xform.rotation;
+            let direction = rotation * Vec3::Z;  // the ship's nose
+
+            // Mines get left behind the ship, everything else comes out the front.
+            let (translation, velocity) = if kind == WeaponKind::Mines {
+                (
+                    spaceship_xform.translation - direction * MINE_DROP_DISTANCE,
+                    spaceship_velocity.value * MINE_VELOCITY_SCALAR,
+                )
+            }
+            else {
+                (
+                    spaceship_xform.translation + direction * MISSILE_FORWARD_SPAWN_SCALAR,
+                    direction * speed,
+                )
+            };
+
+            let missile_xform = Transform::from_translation(translation)
+                .with_rotation(rotation)
+                .with_scale(props.projectile_scale);
+
+            let mut missile = commands.spawn((
+                Name::new(props.projectile_name),
+                MovingObjectBundle {
+                    velocity: Velocity::new(velocity),
+                    acceleration: Acceleration::new(Vec3::ZERO),
+                    rotation: Rotation::new(0.0, 0.0, 0.0),
+                    collider: Collider::new(props.projectile_radius),
+                    model: SceneBundle {
+                        scene: SceneRoot(scene_assets.scene(props.scene)),
+                        transform: missile_xform,
+                    },
+                },
+                SpaceshipMissile { weapon: kind },
+                *player,
+                Health::new(MISSILE_HEALTH),
+                CollisionDamage::new(props.damage * damage_scale),
+                Mass::new(MISSILE_MASS),
+            ));
+
+            if props.pierce > 0 {
+                missile.insert(Piercing::new(props.pierce));
+            }
+            if props.ricochet > 0 {
+                missile.insert(Ricochet { bounces: props.ricochet });
+            }
+
+            // Everything has to go away eventually.  Wrapping missiles never
+            // leave the screen and mines don't go anywhere, so those get a
+            // lifetime.  Everything else just runs out of range.
+            if props.wrap_secs > 0.0 {
+                missile.insert((WrapAround, Lifetime::new(props.wrap_secs)));
+            }
+            else if kind == WeaponKind::Mines {
+                missile.insert(Lifetime::new(MINE_LIFETIME_SECS));
+            }
+            else {
+                missile.insert(MaxRange::new(props.max_range));
+            }
+
+            if kind == WeaponKind::Homing {
+                missile.insert(Homing { turn_rate: HOMING_TURN_RATE });
+            }
+        }
+
+        weapon.cooldown.set_duration(std::time::Duration::from_secs_f32(1.0 / fire_rate));
+        weapon.cooldown.reset();
+
+        let slot = weapon.slot_mut(kind);
+        if let Some(ammo) = slot.ammo.as_mut() {
+            *ammo -= 1;
+        }
+        slot.heat += props.heat_per_shot;
+        if slot.heat >= 1.0 {
+            slot.overheated = true;
+
+            #[cfg(debug_assertions)]
+            info!("{} overheated", props.name);
+        }
+
+        sound_event_writer.write(ShootingSoundEvent { sound: props.sound });
+    }
+}
+
+fn tick_weapons(
+    mut query: Query<&mut Weapon>,
+    time: Res<Time>,
+) {
+    for mut weapon in query.iter_mut() {
+        for kind in WeaponKind::ALL {
+            let max_ammo = kind.props().max_ammo;
+            let slot = weapon.slot_mut(kind);
+
+            slot.heat = (slot.heat - HEAT_COOL_RATE * time.delta_secs()).max(0.0);
+            if slot.overheated && slot.heat < HEAT_RECOVERED {
+                slot.overheated = false;
+            }
+
+            if let (Some(ammo), Some(max)) = (slot.ammo.as_mut(), max_ammo) {
+                if *ammo < max && slot.ammo_timer.tick(time.delta()).just_finished() {
+                    *ammo += 1;
+                }
+            }
+        }
+    }
+}
+
+type HomingTarget = (Or<(With<Asteroid>, With<Saucer>)>, Without<Homing>);
+
+fn homing_guidance(
+    mut missiles: Query<(&Homing, &mut Transform, &mut Velocity)>,
+    targets: Query<&Transform, HomingTarget>,
+    time: Res<Time>,
+) {
+    for (homing, mut xform, mut velocity) in missiles.iter_mut() {
+        let position = xform.translation;
+
+        let Some(target) = targets
+            .iter()
+            .map(|target| target.translation)
+            .filter(|target| target.distance(position) < HOMING_RANGE)
+            .min_by(|a, b| a.distance(position).total_cmp(&b.distance(position)))
+        else {
+            continue;
+        };
+
+        let speed = velocity.value.length();
+        let current = velocity.value.normalize_or_zero();
+        let desired = (target - position).normalize_or_zero();
+        if current == Vec3::ZERO || desired == Vec3::ZERO {
+            continue;
+        }
+
+        // Turn toward the target, but no faster than the turn rate.
+        let angle = current.angle_between(desired);
+        let max_turn = homing.turn_rate * time.delta_secs();
+        let t = if angle > max_turn { max_turn / angle } else { 1.0 };
+        let direction = Quat::IDENTITY.slerp(Quat::from_rotation_arc(current, desired), t) * current;
+
+        velocity.value = direction * speed;
+        xform.rotation = Quat::from_rotation_arc(Vec3::Z, direction);
+    }
+}
@@ -1,23 +1,37 @@
-use bevy::prelude::*;
+use bevy::{ecs::schedule::ScheduleLabel, prelude::*};
 
 
 #[derive(Debug, Default, Hash, Eq, PartialEq, Clone, States)]
 pub enum GameState {
     #[default]
+    Loading,
+    LoadingFailed,
     InGame,
     Paused,
+    Shop,
     GameOver,
     StartGame,
     QuitGame,
 }
 
 
+// Spawns the ships, the HUD and everything else that sticks around from one
+// game to the next.  It runs once, on the way into the game from Loading (or
+// LoadingFailed), so it has the assets to hand and a retried load doesn't
+// spawn it all twice.
+#[derive(ScheduleLabel, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct GameSetup;
+
+
 pub struct StatePlugin;
 
 impl Plugin for StatePlugin {
     fn build(&self, app: &mut App) {
         app
         .init_state::<GameState>()
+        .init_schedule(GameSetup)
+        .add_systems(OnTransition { exited: GameState::Loading, entered: GameState::InGame }, run_game_setup)
+        .add_systems(OnTransition { exited: GameState::LoadingFailed, entered: GameState::InGame }, run_game_setup)
         .add_systems(Update,
             (
                 game_state_input_events,
@@ -41,6 +55,71 @@ pub fn game_state_input_events(
     }
 }
 
+fn run_game_setup(world: &mut World) {
+    world.run_schedule(GameSetup);
+}
+
 fn transition_to_in_game(mut next_state: ResMut<NextState<GameState>>) {
     next_state.set(GameState::InGame);
 }
+
+
+#[cfg(test)]
+mod tests {
+    use bevy::state::app::StatesPlugin;
+
+    use super::*;
+
+    #[derive(Resource, Default)]
+    struct SetupRuns(u32);
+
+    fn count_setup(mut runs: ResMut<SetupRuns>) {
+        runs.0 += 1;
+    }
+
+    fn app() -> App {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, StatesPlugin, StatePlugin))
+        .init_resource::<ButtonInput<KeyCode>>()
+        .init_resource::<SetupRuns>()
+        .add_systems(GameSetup, count_setup);
+        app.update();
+        app
+    }
+
+    fn go_to(app: &mut App, state: GameState) {
+        app.world_mut().resource_mut::<NextState<GameState>>().set(state);
+        app.update();
+    }
+
+    #[test]
+    fn setup_runs_once_loading_straight_through() {
+        let mut app = app();
+        go_to(&mut app, GameState::InGame);
+        go_to(&mut app, GameState::GameOver);
+        go_to(&mut app, GameState::StartGame);
+        app.update();
+
+        assert_eq!(*app.world().resource::<State<GameState>>().get(), GameState::InGame);
+        assert_eq!(app.world().resource::<SetupRuns>().0, 1);
+    }
+
+    #[test]
+    fn setup_runs_once_after_retrying_a_failed_load() {
+        let mut app = app();
+        go_to(&mut app, GameState::LoadingFailed);
+        assert_eq!(app.world().resource::<SetupRuns>().0, 0);
+
+        go_to(&mut app, GameState::Loading);
+        go_to(&mut app, GameState::InGame);
+        assert_eq!(app.world().resource::<SetupRuns>().0, 1);
+    }
+
+    #[test]
+    fn setup_runs_when_playing_on_without_some_assets() {
+        let mut app = app();
+        go_to(&mut app, GameState::LoadingFailed);
+        go_to(&mut app, GameState::InGame);
+        assert_eq!(app.world().resource::<SetupRuns>().0, 1);
+    }
+}
@@ -0,0 +1,318 @@
+use bevy::{platform::collections::{HashMap, HashSet}, prelude::*};
+
+use crate::{
+    app_globals::AppGlobals,
+    asset_loader::SceneAssets,
+    asteroids::{levels::wave_definition, WaveStartedEvent},
+    collision_detection::CollisionEvent,
+    difficulty::Difficulty,
+    health::Health,
+    lifetime::Lifetime,
+    players::TeamScore,
+    schedule::InGameSet,
+    spaceship::{projectiles::MissileTarget, Spaceship, SpaceshipMissile},
+    state::{GameSetup, GameState},
+};
+
+const WAVE_COUNTDOWN_SECS: f32 = 3.0;
+
+// The wave-clear bonus.
+const ACCURACY_BONUS: f32 = 100.0;  // for hitting with every shot
+const PAR_TIME_SECS: f32 = 60.0;
+const TIME_BONUS_PER_SEC: f32 = 2.0;  // for every second under par
+const NO_DAMAGE_BONUS: i32 = 100;
+
+const BANNER_COLOR: Color = Color::srgb(0.9, 0.9, 0.9);
+const BOSS_BANNER_COLOR: Color = Color::srgb(1.0, 0.3, 0.2);
+const SUMMARY_BONUS_COLOR: Color = Color::srgb(1.0, 0.8, 0.1);
+
+
+// Asteroids starts this once the wave has been cleared (and the shop has
+// been and gone), and launches the next wave when it runs out.
+#[derive(Resource, Debug)]
+pub struct WaveCountdown {
+    pub timer: Timer,
+    pub active: bool,
+    pub cleared: bool,  // the clear summary has already been shown
+}
+
+impl Default for WaveCountdown {
+    fn default() -> Self {
+        Self {
+            timer: Timer::from_seconds(WAVE_COUNTDOWN_SECS, TimerMode::Once),
+            active: false,
+            cleared: false,
+        }
+    }
+}
+
+impl WaveCountdown {
+    pub fn start(&mut self) {
+        self.timer.reset();
+        self.active = true;
+    }
+}
+
+#[derive(Message, Debug)]
+pub struct WaveClearedEvent {
+    pub level: i32,
+}
+
+// How the player (or the team, in co-op) is doing in the current wave.
+#[derive(Resource, Debug, Default)]
+pub struct WaveStats {
+    pub shots: u32,
+    pub hits: HashSet<Entity>,  // missiles that hit something
+    pub damage_taken: f32,
+    pub secs: f32,
+    last_ship_health: HashMap<Entity, f32>,  // for each ship
+}
+
+impl WaveStats {
+    pub fn accuracy(&self) -> f32 {
+        if self.shots == 0 {
+            return 0.0;
+        }
+        (self.hits.len() as f32 / self.shots as f32).min(1.0)
+    }
+
+    pub fn bonus(&self) -> i32 {
+        let accuracy = (self.accuracy() * ACCURACY_BONUS) as i32;
+        let time = ((PAR_TIME_SECS - self.secs).max(0.0) * TIME_BONUS_PER_SEC) as i32;
+        let no_damage = if self.damage_taken <= 0.0 { NO_DAMAGE_BONUS } else { 0 };
+
+        accuracy + time + no_damage
+    }
+}
+
+#[derive(Component, Debug)]
+pub struct WaveBanner;
+
+#[derive(Component, Debug)]
+pub struct WaveBannerTitle;
+
+#[derive(Component, Debug)]
+pub struct WaveBannerCountdown;
+
+
+pub struct WavePlugin;
+
+impl Plugin for WavePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<WaveCountdown>()
+        .init_resource::<WaveStats>()
+        .add_message::<WaveClearedEvent>()
+        .add_systems(GameSetup, spawn_wave_banner)
+        .add_systems(OnEnter(GameState::StartGame), reset_waves)
+        .add_systems(Update, (
+                // Clear out the last wave's stats before counting anything
+                // for the new one.
+                (
+                    reset_wave_stats,
+                    (count_shots, count_hits, track_damage_taken),
+                ).chain(),
+                show_wave_summary,
+                update_wave_banner,
+            ).in_set(InGameSet::EntityUpdates),
+        )
+        .add_systems(OnEnter(GameState::GameOver), hide_wave_banner);
+    }
+}
+
+fn reset_waves(
+    mut countdown: ResMut<WaveCountdown>,
+    mut stats: ResMut<WaveStats>,
+) {
+    *countdown = WaveCountdown::default();
+    *stats = WaveStats::default();
+}
+
+fn reset_wave_stats(
+    mut wave_started_reader: MessageReader<WaveStartedEvent>,
+    mut stats: ResMut<WaveStats>,
+) {
+    if wave_started_reader.read().count() > 0 {
+        *stats = WaveStats::default();
+    }
+}
+
+fn count_shots(
+    missiles: Query<(), Added<SpaceshipMissile>>,
+    mut stats: ResMut<WaveStats>,
+    time: Res<Time>,
+) {
+    stats.shots += missiles.iter().count() as u32;
+    stats.secs += time.delta_secs();
+}
+
+fn count_hits(
+    mut collision_event_reader: MessageReader<CollisionEvent>,
+    missiles: Query<(), With<SpaceshipMissile>>,
+    targets: Query<(), MissileTarget>,
+    mut stats: ResMut<WaveStats>,
+) {
+    for event in collision_event_reader.read() {
+        if missiles.contains(event.collided_entity) && targets.contains(event.entity) {
+            stats.hits.insert(event.collided_entity);
+        }
+    }
+}
+
+fn track_damage_taken(
+    ship_q: Query<(Entity, &Health), With<Spaceship>>,
+    mut stats: ResMut<WaveStats>,
+) {
+    for (ship_e, health) in ship_q.iter() {
+        // Repairs don't count against it.
+        if let Some(&last) = stats.last_ship_health.get(&ship_e) {
+            if health.value < last {
+                stats.damage_taken += last - health.value;
+            }
+        }
+        stats.last_ship_health.insert(ship_e, health.value);
+    }
+}
+
+fn spawn_wave_banner(
+    mut commands: Commands,
+    scene_assets: Res<SceneAssets>,
+) {
+    let font = |size: f32| TextFont {
+        font: scene_assets.font("font"),
+        font_size: size,
+        ..default()
+    };
+
+    commands.spawn((
+        Name::new("wave_banner"),
+        Node {
+            position_type: PositionType::Absolute,
+            width: Val::Percent(100.),
+            top: Val::Percent(30.),
+            display: Display::None,
+            flex_direction: FlexDirection::Column,
+            align_items: AlignItems::Center,
+            ..default()
+        },
+        WaveBanner,
+    ))
+    .with_children(|builder| {
+        builder.spawn((
+            Text::new(""),
+            font(48.0),
+            TextColor(BANNER_COLOR),
+            WaveBannerTitle,
+        ));
+        builder.spawn((
+            Text::new(""),
+            font(36.0),
+            TextColor(BANNER_COLOR),
+            WaveBannerCountdown,
+        ));
+    });
+}
+
+// Both lines of the banner are Text, so each query has to rule out the other.
+type BannerTitleOnly = (With<WaveBannerTitle>, Without<WaveBannerCountdown>);
+type BannerCountdownOnly = (With<WaveBannerCountdown>, Without<WaveBannerTitle>);
+
+fn update_wave_banner(
+    countdown: Res<WaveCountdown>,
+    app_globals: Res<AppGlobals>,
+    mut banner_q: Query<&mut Node, With<WaveBanner>>,
+    mut title_q: Query<(&mut Text, &mut TextColor), BannerTitleOnly>,
+    mut count_q: Query<&mut Text, BannerCountdownOnly>,
+) {
+    let Ok(mut banner) = banner_q.single_mut() else { return; };
+
+    if !countdown.active {
+        banner.display = Display::None;
+        return;
+    }
+    banner.display = Display::Flex;
+
+    // The level hasn't been bumped yet, so it is the wave that is coming.
+    let level = app_globals.level;
+
+    if let Ok((mut text, mut color)) = title_q.single_mut() {
+        if wave_definition(level).boss {
+            text.0 = format!("Wave {} - BOSS", level);
+            color.0 = BOSS_BANNER_COLOR;
+        }
+        else {
+            text.0 = format!("Wave {}", level);
+            color.0 = BANNER_COLOR;
+        }
+    }
+
+    if let Ok(mut text) = count_q.single_mut() {
+        text.0 = format!("{:.0}", countdown.timer.remaining_secs().ceil());
+    }
+}
+
+fn hide_wave_banner(
+    mut banner_q: Query<&mut Node, With<WaveBanner>>,
+) {
+    for mut banner in banner_q.iter_mut() {
+        banner.display = Display::None;
+    }
+}
+
+fn show_wave_summary(
+    mut commands: Commands,
+    mut wave_cleared_reader: MessageReader<WaveClearedEvent>,
+    stats: Res<WaveStats>,
+    mut team_score: TeamScore,
+    scene_assets: Res<SceneAssets>,
+    difficulty: Res<Difficulty>,
+) {
+    let font = |size: f32| TextFont {
+        font: scene_assets.font("font"),
+        font_size: size,
+        ..default()
+    };
+
+    for event in wave_cleared_reader.read() {
+        // The whole team cleared the wave, so everybody gets a share.
+        let bonus = stats.bonus();
+        team_score.share(bonus);
+
+        #[cfg(debug_assertions)]
+        info!("Wave {} cleared: {:?}, bonus {}", event.level, stats, bonus);
+
+        // This goes away on its own, just as the next wave is announced.
+        commands.spawn((
+            Name::new("wave_summary"),
+            Node {
+                position_type: PositionType::Absolute,
+                width: Val::Percent(100.),
+                top: Val::Percent(30.),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                row_gap: Val::Px(6.0),
+                ..default()
+            },
+            Lifetime::new(difficulty.settings().asteroid_spawn_secs),
+        ))
+        .with_children(|builder| {
+            builder.spawn((
+                Text::new(format!("Wave {} cleared!", event.level)),
+                font(40.0),
+            ));
+            builder.spawn((
+                Text::new(format!(
+                    "Accuracy: {:.0}%   Time: {:.0}s   Damage taken: {:.0}",
+                    stats.accuracy() * 100.0,
+                    stats.secs,
+                    stats.damage_taken,
+                )),
+                font(20.0),
+            ));
+            builder.spawn((
+                Text::new(format!("Bonus: {}", bonus)),
+                font(24.0),
+                TextColor(SUMMARY_BONUS_COLOR),
+            ));
+        });
+    }
+}